@@ -0,0 +1,76 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CaptureLength {
+    /// the whole history
+    Buffer,
+    /// the last whole bar, ending on the last downbeat
+    Bar,
+}
+
+impl CaptureLength {
+    pub fn get_symbol(&self) -> &str {
+        match self {
+            CaptureLength::Buffer => "buf",
+            CaptureLength::Bar => "bar",
+        }
+    }
+
+    pub fn toggle(&self) -> Self {
+        match self {
+            CaptureLength::Buffer => CaptureLength::Bar,
+            CaptureLength::Bar => CaptureLength::Buffer,
+        }
+    }
+}
+
+/// Continuously running recording of the input, so a
+/// moment can be captured into a step after it happened
+pub struct InputHistory {
+    ring: Vec<f32>,
+    /// length of a capture of the whole history
+    capture_len: usize,
+    write_head: usize,
+    /// samples written since the last downbeat
+    since_downbeat: usize,
+}
+
+impl InputHistory {
+    /// Keeps twice `capture_len`, so a bar still fits when the
+    /// last downbeat was a while ago
+    pub fn new(capture_len: usize) -> Self {
+        InputHistory {
+            ring: vec![0.0; capture_len * 2],
+            capture_len,
+            write_head: 0,
+            since_downbeat: 0,
+        }
+    }
+
+    pub fn push(&mut self, sample: f32) {
+        self.ring[self.write_head] = sample;
+        self.write_head = (self.write_head + 1) % self.ring.len();
+        self.since_downbeat += 1;
+    }
+
+    pub fn downbeat(&mut self) {
+        self.since_downbeat = 0;
+    }
+
+    /// Copies the captured audio to the start of `dest` and returns its length
+    pub fn copy_to(&self, dest: &mut [f32], length: CaptureLength, beat_len: usize) -> usize {
+        let ring_len = self.ring.len();
+        let (len, end_offset) = match length {
+            CaptureLength::Buffer => (self.capture_len.min(dest.len()), 0),
+            CaptureLength::Bar => {
+                // bars longer than the history are cut at their start
+                let len = (beat_len.max(1) * 4).min(ring_len).min(dest.len());
+                (len, self.since_downbeat.min(ring_len - len))
+            }
+        };
+
+        let start = (self.write_head + 2 * ring_len - end_offset - len) % ring_len;
+        for (i, sample) in dest.iter_mut().take(len).enumerate() {
+            *sample = self.ring[(start + i) % ring_len];
+        }
+        len
+    }
+}
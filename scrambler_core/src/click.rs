@@ -0,0 +1,43 @@
+use std::f32::consts::PI;
+
+const CLICK_SEC: f32 = 0.03;
+const CLICK_GAIN: f32 = 0.3;
+
+/// Metronome blip for the record count-in
+pub struct Click {
+    phase: f32,
+    freq: f32,
+    remaining: usize,
+    length: usize,
+    sample_rate: f32,
+}
+
+impl Click {
+    pub fn new(sample_rate: f32) -> Self {
+        Click {
+            phase: 0.0,
+            freq: 1000.0,
+            remaining: 0,
+            length: (CLICK_SEC * sample_rate) as usize,
+            sample_rate,
+        }
+    }
+
+    /// Starts a click, the first beat of a bar is pitched up
+    pub fn trigger(&mut self, accent: bool) {
+        self.freq = if accent { 1500.0 } else { 1000.0 };
+        self.phase = 0.0;
+        self.remaining = self.length;
+    }
+
+    pub fn process(&mut self) -> f32 {
+        if self.remaining == 0 {
+            return 0.0;
+        }
+        self.remaining -= 1;
+        let env = self.remaining as f32 / self.length as f32;
+        let sample = (2.0 * PI * self.phase).sin() * env * env * CLICK_GAIN;
+        self.phase = (self.phase + self.freq / self.sample_rate).fract();
+        sample
+    }
+}
@@ -2,4 +2,12 @@ pub const SEQUENCES: i32 = 3;
 pub const STEP_NUM: u8 = 8;
 pub const INIT_BPM: f32 = 100.0;
 pub const AUDIO_BUFFER_SIZE_SEC: f32 = 2.0;
-pub const VOICE_NUM: u8 = 32;
+pub const MAX_RECORD_SEC: f32 = 8.0;
+pub const MAX_POLYPHONY: u8 = 64;
+pub const MAX_GRAINS: u8 = 16;
+pub const MAX_DELAY_SEC: f32 = 4.0;
+pub const DECLICK_SEC: f32 = 0.005;
+pub const CHOKE_GROUPS: u8 = 4;
+pub const LFO_NUM: u8 = 4;
+pub const MOD_SLOTS: u8 = 4;
+pub const PITCH_STEPS: u8 = 8;
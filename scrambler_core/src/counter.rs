@@ -23,7 +23,12 @@ impl Counter {
             self.phase = 0.0;
             return true;
         }
-        return false;
+        false
+    }
+
+    /// Progress towards the next tick, 0.0 - 1.0
+    pub fn phase(&self) -> f32 {
+        self.phase
     }
 
     pub fn set_frequency(&mut self, freq: f32) {
@@ -0,0 +1,60 @@
+use crate::{Subdivision, INIT_BPM, MAX_DELAY_SEC};
+
+/// Time in seconds the delay needs to glide to a new delay time
+const GLIDE_SEC: f32 = 0.05;
+
+/// Tempo synced feedback delay
+pub struct Delay {
+    buffer: Vec<f32>,
+    write_head: usize,
+    pub subdivision: Subdivision,
+    pub feedback: f32,
+    time: f32,
+    target_time: f32,
+    glide: f32,
+    sample_rate: f32,
+}
+
+impl Delay {
+    pub fn new(sample_rate: f32) -> Self {
+        let subdivision = Subdivision::DottedEighth;
+        let time = sample_rate / subdivision.to_hz(INIT_BPM);
+        Delay {
+            buffer: vec![0.0; (sample_rate * MAX_DELAY_SEC) as usize],
+            write_head: 0,
+            subdivision,
+            feedback: 0.4,
+            time,
+            target_time: time,
+            glide: 1.0 - (-1.0 / (GLIDE_SEC * sample_rate)).exp(),
+            sample_rate,
+        }
+    }
+
+    pub fn set_bpm(&mut self, bpm: f32) {
+        let max_time = (self.buffer.len() - 2) as f32;
+        self.target_time = (self.sample_rate / self.subdivision.to_hz(bpm)).min(max_time);
+    }
+
+    pub fn set_subdivision(&mut self, subdivision: Subdivision, bpm: f32) {
+        self.subdivision = subdivision;
+        self.set_bpm(bpm);
+    }
+
+    pub fn process(&mut self, sample: f32) -> f32 {
+        // glide towards the new time instead of jumping, which would click
+        self.time += (self.target_time - self.time) * self.glide;
+
+        let len = self.buffer.len();
+        let read_head = self.write_head as f32 + len as f32 - self.time;
+        let pos_int = read_head as usize;
+        let frac = read_head - pos_int as f32;
+        let out =
+            self.buffer[pos_int % len] * (1.0 - frac) + self.buffer[(pos_int + 1) % len] * frac;
+
+        self.buffer[self.write_head] = sample + out * self.feedback;
+        self.write_head = (self.write_head + 1) % len;
+
+        out
+    }
+}
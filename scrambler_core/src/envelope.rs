@@ -0,0 +1,177 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GateLength {
+    /// fraction of the sequence's subdivision
+    Fraction(f32),
+    Ms(f32),
+}
+
+impl GateLength {
+    /// Gate length in seconds for a step of `step_sec` seconds
+    pub fn to_sec(self, step_sec: f32) -> f32 {
+        match self {
+            GateLength::Fraction(fraction) => fraction * step_sec,
+            GateLength::Ms(ms) => ms / 1000.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnvCurve {
+    Linear,
+    /// moves fast at the start of a segment and settles slowly, like an RC circuit
+    Exponential,
+    /// moves slowly at the start of a segment and fast at its end
+    Logarithmic,
+    SCurve,
+}
+
+impl EnvCurve {
+    pub fn get_symbol(&self) -> &str {
+        match self {
+            EnvCurve::Linear => "lin",
+            EnvCurve::Exponential => "exp",
+            EnvCurve::Logarithmic => "log",
+            EnvCurve::SCurve => "s",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            EnvCurve::Linear => EnvCurve::Exponential,
+            EnvCurve::Exponential => EnvCurve::Logarithmic,
+            EnvCurve::Logarithmic => EnvCurve::SCurve,
+            EnvCurve::SCurve => EnvCurve::Linear,
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        match self {
+            EnvCurve::Linear => EnvCurve::SCurve,
+            EnvCurve::Exponential => EnvCurve::Linear,
+            EnvCurve::Logarithmic => EnvCurve::Exponential,
+            EnvCurve::SCurve => EnvCurve::Logarithmic,
+        }
+    }
+
+    /// Maps the linear progress of a segment to the travelled distance
+    fn shape(&self, progress: f32) -> f32 {
+        match self {
+            EnvCurve::Linear => progress,
+            EnvCurve::Exponential => 1.0 - (1.0 - progress).powi(3),
+            EnvCurve::Logarithmic => progress.powi(3),
+            EnvCurve::SCurve => progress * progress * (3.0 - 2.0 * progress),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// ADSR envelope whose release starts once the gate has run out
+pub struct Envelope {
+    stage: Stage,
+    pub value: f32,
+    start: f32,
+    progress: f32,
+    attack_inc: f32,
+    decay_inc: f32,
+    release_inc: f32,
+    sustain: f32,
+    curve: EnvCurve,
+    gate: usize,
+    gate_counter: usize,
+    sample_rate: f32,
+}
+
+impl Envelope {
+    pub fn new(sample_rate: f32) -> Self {
+        Envelope {
+            stage: Stage::Idle,
+            value: 0.0,
+            start: 0.0,
+            progress: 0.0,
+            attack_inc: 1.0,
+            decay_inc: 1.0,
+            release_inc: 1.0,
+            sustain: 1.0,
+            curve: EnvCurve::Linear,
+            gate: 0,
+            gate_counter: 0,
+            sample_rate,
+        }
+    }
+
+    fn inc(&self, sec: f32) -> f32 {
+        1.0 / (sec * self.sample_rate).max(1.0)
+    }
+
+    /// Starts the envelope, times are given in seconds
+    pub fn trigger(
+        &mut self,
+        attack: f32,
+        decay: f32,
+        sustain: f32,
+        release: f32,
+        gate: f32,
+        curve: EnvCurve,
+    ) {
+        self.curve = curve;
+        self.attack_inc = self.inc(attack);
+        self.decay_inc = self.inc(decay);
+        self.release_inc = self.inc(release);
+        self.sustain = sustain;
+        self.gate = (gate * self.sample_rate) as usize;
+        self.gate_counter = 0;
+        self.enter(Stage::Attack);
+    }
+
+    pub fn reset(&mut self) {
+        self.stage = Stage::Idle;
+        self.value = 0.0;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.stage == Stage::Idle
+    }
+
+    fn enter(&mut self, stage: Stage) {
+        self.stage = stage;
+        self.start = self.value;
+        self.progress = 0.0;
+    }
+
+    pub fn update(&mut self) -> f32 {
+        self.gate_counter += 1;
+        if self.gate_counter >= self.gate && self.stage != Stage::Release && !self.is_finished() {
+            self.enter(Stage::Release);
+        }
+
+        let (inc, target) = match self.stage {
+            Stage::Idle => return 0.0,
+            Stage::Attack => (self.attack_inc, 1.0),
+            Stage::Decay => (self.decay_inc, self.sustain),
+            Stage::Sustain => return self.value,
+            Stage::Release => (self.release_inc, 0.0),
+        };
+
+        self.progress = (self.progress + inc).min(1.0);
+        self.value = self.start + (target - self.start) * self.curve.shape(self.progress);
+
+        if self.progress >= 1.0 {
+            match self.stage {
+                Stage::Attack => self.enter(Stage::Decay),
+                Stage::Decay => self.enter(Stage::Sustain),
+                Stage::Release => self.stage = Stage::Idle,
+                _ => {}
+            }
+        }
+
+        self.value
+    }
+}
@@ -0,0 +1,104 @@
+use std::f32::consts::PI;
+
+/// Range of the filter envelope in octaves at full envelope amount
+const ENV_OCTAVES: f32 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterMode {
+    LowPass,
+    HighPass,
+    BandPass,
+    Notch,
+}
+
+impl FilterMode {
+    pub fn get_symbol(&self) -> &str {
+        match self {
+            FilterMode::LowPass => "lp",
+            FilterMode::HighPass => "hp",
+            FilterMode::BandPass => "bp",
+            FilterMode::Notch => "notch",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            FilterMode::LowPass => FilterMode::HighPass,
+            FilterMode::HighPass => FilterMode::BandPass,
+            FilterMode::BandPass => FilterMode::Notch,
+            FilterMode::Notch => FilterMode::LowPass,
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        match self {
+            FilterMode::LowPass => FilterMode::Notch,
+            FilterMode::HighPass => FilterMode::LowPass,
+            FilterMode::BandPass => FilterMode::HighPass,
+            FilterMode::Notch => FilterMode::BandPass,
+        }
+    }
+}
+
+/// Trapezoidal state variable filter with a decaying envelope on the cutoff
+pub struct Filter {
+    pub mode: FilterMode,
+    pub cutoff: f32,
+    pub resonance: f32,
+    pub env_amount: f32,
+    /// cutoff offset in octaves
+    pub modulation: f32,
+    env: f32,
+    env_coef: f32,
+    ic1eq: f32,
+    ic2eq: f32,
+    sample_rate: f32,
+}
+
+impl Filter {
+    pub fn new(sample_rate: f32) -> Self {
+        Filter {
+            mode: FilterMode::LowPass,
+            cutoff: 20000.0,
+            resonance: 0.0,
+            env_amount: 0.0,
+            modulation: 0.0,
+            env: 0.0,
+            env_coef: 0.0,
+            ic1eq: 0.0,
+            ic2eq: 0.0,
+            sample_rate,
+        }
+    }
+
+    /// Restarts the envelope, decaying to zero over roughly `decay` seconds
+    pub fn trigger(&mut self, decay: f32) {
+        self.env = 1.0;
+        self.env_coef = (-5.0 / (decay * self.sample_rate)).exp();
+    }
+
+    pub fn process(&mut self, sample: f32) -> f32 {
+        let octaves = self.env * self.env_amount * ENV_OCTAVES + self.modulation;
+        let cutoff = (self.cutoff * 2.0f32.powf(octaves)).clamp(20.0, self.sample_rate * 0.45);
+        self.env *= self.env_coef;
+
+        let g = (PI * cutoff / self.sample_rate).tan();
+        let k = 2.0 - 1.95 * self.resonance;
+        let a1 = 1.0 / (1.0 + g * (g + k));
+        let a2 = g * a1;
+        let a3 = g * a2;
+
+        let v3 = sample - self.ic2eq;
+        let v1 = a1 * self.ic1eq + a2 * v3;
+        let v2 = self.ic2eq + a2 * self.ic1eq + a3 * v3;
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+
+        match self.mode {
+            FilterMode::LowPass => v2,
+            FilterMode::HighPass => sample - k * v1 - v2,
+            FilterMode::BandPass => v1,
+            FilterMode::Notch => sample - k * v1,
+        }
+    }
+}
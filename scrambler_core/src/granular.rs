@@ -0,0 +1,233 @@
+use std::f32::consts::PI;
+
+use crate::{Counter, Interpolation, Random, Scale, MAX_GRAINS};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VoiceMode {
+    OneShot,
+    Granular,
+}
+
+impl VoiceMode {
+    pub fn get_symbol(&self) -> &str {
+        match self {
+            VoiceMode::OneShot => "one",
+            VoiceMode::Granular => "gran",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            VoiceMode::OneShot => VoiceMode::Granular,
+            VoiceMode::Granular => VoiceMode::OneShot,
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        self.next()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GrainWindow {
+    Hann,
+    Triangle,
+    Trapezoid,
+    Gaussian,
+}
+
+impl GrainWindow {
+    pub fn get_symbol(&self) -> &str {
+        match self {
+            GrainWindow::Hann => "hann",
+            GrainWindow::Triangle => "tri",
+            GrainWindow::Trapezoid => "trap",
+            GrainWindow::Gaussian => "gauss",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            GrainWindow::Hann => GrainWindow::Triangle,
+            GrainWindow::Triangle => GrainWindow::Trapezoid,
+            GrainWindow::Trapezoid => GrainWindow::Gaussian,
+            GrainWindow::Gaussian => GrainWindow::Hann,
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        match self {
+            GrainWindow::Hann => GrainWindow::Gaussian,
+            GrainWindow::Triangle => GrainWindow::Hann,
+            GrainWindow::Trapezoid => GrainWindow::Triangle,
+            GrainWindow::Gaussian => GrainWindow::Trapezoid,
+        }
+    }
+
+    /// Window amplitude for a grain phase between 0.0 and 1.0
+    fn gain(&self, phase: f32) -> f32 {
+        match self {
+            GrainWindow::Hann => 0.5 - 0.5 * (2.0 * PI * phase).cos(),
+            GrainWindow::Triangle => 1.0 - (2.0 * phase - 1.0).abs(),
+            GrainWindow::Trapezoid => (phase.min(1.0 - phase) * 8.0).min(1.0),
+            GrainWindow::Gaussian => {
+                let x = (phase - 0.5) / 0.15;
+                (-0.5 * x * x).exp()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GrainParams {
+    /// grain length in seconds
+    pub size: f32,
+    /// grains per second
+    pub density: f32,
+    /// read position in the step buffer, 0.0 - 1.0
+    pub position: f32,
+    /// random offset of the read position, 0.0 - 1.0
+    pub position_jitter: f32,
+    /// random detune of each grain in semitones
+    pub pitch_jitter: f32,
+    pub window: GrainWindow,
+}
+
+impl Default for GrainParams {
+    fn default() -> Self {
+        GrainParams {
+            size: 0.1,
+            density: 20.0,
+            position: 0.0,
+            position_jitter: 0.1,
+            pitch_jitter: 0.0,
+            window: GrainWindow::Hann,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Grain {
+    play_head: f32,
+    pitch: f32,
+    phase: f32,
+    phase_inc: f32,
+    is_playing: bool,
+}
+
+pub struct GrainCloud {
+    grains: Vec<Grain>,
+    params: GrainParams,
+    pitch: f32,
+    scale: Scale,
+    interpolation: Interpolation,
+    /// grains play backwards from their start position
+    reverse: bool,
+    spawner: Counter,
+    spawn_pending: bool,
+    random: Random,
+    sample_rate: f32,
+}
+
+impl GrainCloud {
+    pub fn new(sample_rate: f32, seed: u32) -> Self {
+        let params = GrainParams::default();
+        GrainCloud {
+            grains: vec![
+                Grain {
+                    play_head: 0.0,
+                    pitch: 1.0,
+                    phase: 0.0,
+                    phase_inc: 0.0,
+                    is_playing: false,
+                };
+                MAX_GRAINS as usize
+            ],
+            params,
+            pitch: 1.0,
+            scale: Scale::Off,
+            interpolation: Interpolation::Linear,
+            reverse: false,
+            spawner: Counter::new(sample_rate, params.density),
+            spawn_pending: false,
+            random: Random::new(seed),
+            sample_rate,
+        }
+    }
+
+    pub fn start(
+        &mut self,
+        params: GrainParams,
+        pitch: f32,
+        scale: Scale,
+        interpolation: Interpolation,
+        reverse: bool,
+    ) {
+        self.params = params;
+        self.reverse = reverse;
+        self.pitch = pitch;
+        self.scale = scale;
+        self.interpolation = interpolation;
+        self.spawner.set_frequency(params.density);
+        self.spawner.reset();
+        self.spawn_pending = true;
+        for grain in self.grains.iter_mut() {
+            grain.is_playing = false;
+        }
+    }
+
+    fn spawn(&mut self, buffer_size: usize) {
+        if buffer_size == 0 {
+            return;
+        }
+
+        let jitter = self.random.bipolar() * self.params.position_jitter;
+        let position = (self.params.position + jitter).clamp(0.0, 1.0);
+        // positions count from the end when the buffer plays backwards
+        let position = if self.reverse {
+            1.0 - position
+        } else {
+            position
+        };
+        let detune = self.random.bipolar() * self.params.pitch_jitter;
+        let note = self.scale.transpose(12.0 * self.pitch.log2(), detune);
+        let pitch = 2.0f32.powf(note / 12.0);
+        let phase_inc = 1.0 / (self.params.size * self.sample_rate);
+
+        if let Some(grain) = self.grains.iter_mut().find(|grain| !grain.is_playing) {
+            grain.play_head = position * (buffer_size - 1) as f32;
+            grain.pitch = pitch;
+            grain.phase = 0.0;
+            grain.phase_inc = phase_inc;
+            grain.is_playing = true;
+        }
+    }
+
+    pub fn render(&mut self, buffer: &[f32]) -> f32 {
+        if self.spawner.update() || self.spawn_pending {
+            self.spawn_pending = false;
+            self.spawn(buffer.len());
+        }
+
+        let mut sample = 0.0;
+        for grain in self.grains.iter_mut().filter(|grain| grain.is_playing) {
+            sample += self
+                .interpolation
+                .read(buffer, grain.play_head, grain.pitch)
+                * self.params.window.gain(grain.phase);
+            if self.reverse {
+                grain.play_head -= grain.pitch;
+            } else {
+                grain.play_head += grain.pitch;
+            }
+            grain.phase += grain.phase_inc;
+            if grain.phase >= 1.0 || grain.play_head < 0.0 || grain.play_head >= buffer.len() as f32
+            {
+                grain.is_playing = false;
+            }
+        }
+
+        let overlap = self.params.size * self.params.density;
+        sample / overlap.max(1.0).sqrt()
+    }
+}
@@ -0,0 +1,43 @@
+/// Time the meter takes to fall by 20dB
+const METER_RELEASE_SEC: f32 = 0.3;
+/// Level at which a recording counts as clipped
+const CLIP_LEVEL: f32 = 0.999;
+
+/// Gain stage in front of the recorder, with a monitor send and a level meter
+pub struct InputSection {
+    pub gain: f32,
+    /// level of the input mixed into the output
+    pub monitor: f32,
+    /// peak level after the gain
+    pub level: f32,
+    /// set when the input clipped during the current recording
+    pub clipped: bool,
+    release_coef: f32,
+}
+
+impl InputSection {
+    pub fn new(sample_rate: f32) -> Self {
+        InputSection {
+            gain: 1.0,
+            monitor: 0.0,
+            level: 0.0,
+            clipped: false,
+            release_coef: 0.1f32.powf(1.0 / (METER_RELEASE_SEC * sample_rate)),
+        }
+    }
+
+    /// Applies the gain and updates the meter, `recording` arms the clip indicator
+    pub fn process(&mut self, sample: f32, recording: bool) -> f32 {
+        let sample = sample * self.gain;
+        let level = sample.abs();
+        self.level = if level > self.level {
+            level
+        } else {
+            self.level * self.release_coef
+        };
+        if recording && level >= CLIP_LEVEL {
+            self.clipped = true;
+        }
+        sample
+    }
+}
@@ -0,0 +1,136 @@
+use std::{f32::consts::PI, sync::OnceLock};
+
+/// Zero crossings on each side of the sinc kernel
+const SINC_HALF_TAPS: usize = 8;
+/// Table entries per zero crossing
+const SINC_RESOLUTION: usize = 256;
+/// Above this pitch the kernel stops widening, to bound the cost per sample
+const MAX_SINC_PITCH: f32 = 4.0;
+
+static SINC_TABLE: OnceLock<Vec<f32>> = OnceLock::new();
+
+/// Blackman windowed sinc from 0 to `SINC_HALF_TAPS`
+fn sinc_table() -> &'static [f32] {
+    SINC_TABLE.get_or_init(|| {
+        let len = SINC_HALF_TAPS * SINC_RESOLUTION + 1;
+        (0..len)
+            .map(|i| {
+                let x = i as f32 / SINC_RESOLUTION as f32;
+                let sinc = if i == 0 {
+                    1.0
+                } else {
+                    (PI * x).sin() / (PI * x)
+                };
+                let w = 0.5 + 0.5 * x / SINC_HALF_TAPS as f32;
+                let window = 0.42 - 0.5 * (2.0 * PI * w).cos() + 0.08 * (4.0 * PI * w).cos();
+                sinc * window
+            })
+            .collect()
+    })
+}
+
+/// Builds the sinc table, call this off the audio thread
+pub fn init_interpolation() {
+    sinc_table();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    Linear,
+    Hermite,
+    Sinc,
+}
+
+impl Interpolation {
+    pub fn get_symbol(&self) -> &str {
+        match self {
+            Interpolation::Linear => "lin",
+            Interpolation::Hermite => "herm",
+            Interpolation::Sinc => "sinc",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Interpolation::Linear => Interpolation::Hermite,
+            Interpolation::Hermite => Interpolation::Sinc,
+            Interpolation::Sinc => Interpolation::Linear,
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        match self {
+            Interpolation::Linear => Interpolation::Sinc,
+            Interpolation::Hermite => Interpolation::Linear,
+            Interpolation::Sinc => Interpolation::Hermite,
+        }
+    }
+
+    /// Reads `buffer` at the fractional position `pos`, `pitch` is the
+    /// playback speed and sets the anti-aliasing cutoff of the sinc mode
+    pub fn read(&self, buffer: &[f32], pos: f32, pitch: f32) -> f32 {
+        match self {
+            Interpolation::Linear => linear(buffer, pos),
+            Interpolation::Hermite => hermite(buffer, pos),
+            Interpolation::Sinc => sinc(buffer, pos, pitch),
+        }
+    }
+}
+
+/// Everything outside the buffer is silence, so the end
+/// doesn't interpolate into the start of the recording
+fn sample_at(buffer: &[f32], index: isize) -> f32 {
+    if index < 0 {
+        return 0.0;
+    }
+    buffer.get(index as usize).copied().unwrap_or(0.0)
+}
+
+pub fn linear(buffer: &[f32], pos: f32) -> f32 {
+    let pos_int = pos.floor() as isize;
+    let frac = pos - pos_int as f32;
+
+    sample_at(buffer, pos_int) * (1.0 - frac) + sample_at(buffer, pos_int + 1) * frac
+}
+
+/// 4 point, 3rd order Hermite
+fn hermite(buffer: &[f32], pos: f32) -> f32 {
+    let pos_int = pos.floor() as isize;
+    let frac = pos - pos_int as f32;
+
+    let xm1 = sample_at(buffer, pos_int - 1);
+    let x0 = sample_at(buffer, pos_int);
+    let x1 = sample_at(buffer, pos_int + 1);
+    let x2 = sample_at(buffer, pos_int + 2);
+
+    let c1 = 0.5 * (x1 - xm1);
+    let c2 = xm1 - 2.5 * x0 + 2.0 * x1 - 0.5 * x2;
+    let c3 = 0.5 * (x2 - xm1) + 1.5 * (x0 - x1);
+
+    ((c3 * frac + c2) * frac + c1) * frac + x0
+}
+
+/// Windowed sinc, the kernel widens with the pitch so that
+/// everything above the new nyquist is filtered out
+fn sinc(buffer: &[f32], pos: f32, pitch: f32) -> f32 {
+    let table = sinc_table();
+    let cutoff = 1.0 / pitch.clamp(1.0, MAX_SINC_PITCH);
+    let reach = SINC_HALF_TAPS as f32 / cutoff;
+
+    let pos_int = pos.floor() as isize;
+    let first = pos_int - reach as isize + 1;
+    let last = pos_int + reach as isize;
+
+    let mut sample = 0.0;
+    for index in first..=last {
+        let x = (pos - index as f32).abs() * cutoff * SINC_RESOLUTION as f32;
+        let table_int = x as usize;
+        if table_int + 1 >= table.len() {
+            continue;
+        }
+        let frac = x - table_int as f32;
+        let weight = table[table_int] * (1.0 - frac) + table[table_int + 1] * frac;
+        sample += sample_at(buffer, index) * weight;
+    }
+    sample * cutoff
+}
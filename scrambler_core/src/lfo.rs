@@ -0,0 +1,116 @@
+use std::f32::consts::PI;
+
+use crate::{ModTarget, Random, Subdivision};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LfoShape {
+    Sine,
+    Triangle,
+    Square,
+    Saw,
+    SampleAndHold,
+}
+
+impl LfoShape {
+    pub fn get_symbol(&self) -> &str {
+        match self {
+            LfoShape::Sine => "sine",
+            LfoShape::Triangle => "tri",
+            LfoShape::Square => "sqr",
+            LfoShape::Saw => "saw",
+            LfoShape::SampleAndHold => "s&h",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            LfoShape::Sine => LfoShape::Triangle,
+            LfoShape::Triangle => LfoShape::Square,
+            LfoShape::Square => LfoShape::Saw,
+            LfoShape::Saw => LfoShape::SampleAndHold,
+            LfoShape::SampleAndHold => LfoShape::Sine,
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        match self {
+            LfoShape::Sine => LfoShape::SampleAndHold,
+            LfoShape::Triangle => LfoShape::Sine,
+            LfoShape::Square => LfoShape::Triangle,
+            LfoShape::Saw => LfoShape::Square,
+            LfoShape::SampleAndHold => LfoShape::Saw,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LfoRate {
+    Sync(Subdivision),
+    Hz(f32),
+}
+
+impl LfoRate {
+    pub fn to_hz(self, bpm: f32) -> f32 {
+        match self {
+            LfoRate::Sync(subdivision) => subdivision.to_hz(bpm),
+            LfoRate::Hz(hz) => hz,
+        }
+    }
+}
+
+pub struct Lfo {
+    pub shape: LfoShape,
+    pub rate: LfoRate,
+    pub depth: f32,
+    /// sequence and parameter this lfo modulates
+    pub target: Option<(usize, ModTarget)>,
+    pub value: f32,
+    phase: f32,
+    random: Random,
+    sample_rate: f32,
+}
+
+impl Lfo {
+    pub fn new(sample_rate: f32, seed: u32) -> Self {
+        Lfo {
+            shape: LfoShape::Sine,
+            rate: LfoRate::Sync(Subdivision::Quarter),
+            depth: 0.5,
+            target: None,
+            value: 0.0,
+            phase: 0.0,
+            random: Random::new(seed),
+            sample_rate,
+        }
+    }
+
+    /// Advances the lfo and returns its bipolar output
+    pub fn update(&mut self, bpm: f32) -> f32 {
+        self.phase += self.rate.to_hz(bpm) / self.sample_rate;
+        let wrapped = self.phase >= 1.0;
+        if wrapped {
+            self.phase -= 1.0;
+        }
+
+        self.value = match self.shape {
+            LfoShape::Sine => (2.0 * PI * self.phase).sin(),
+            LfoShape::Triangle => 1.0 - 4.0 * (self.phase - 0.5).abs(),
+            LfoShape::Square => {
+                if self.phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            LfoShape::Saw => 2.0 * self.phase - 1.0,
+            LfoShape::SampleAndHold => {
+                if wrapped {
+                    self.random.bipolar()
+                } else {
+                    self.value
+                }
+            }
+        };
+        self.value
+    }
+}
@@ -1,24 +1,61 @@
+mod capture;
+mod click;
 mod constants;
 mod counter;
+mod delay;
+mod envelope;
+mod filter;
+mod granular;
+mod input;
+mod interpolation;
+mod lfo;
+mod master;
+mod matrix;
+mod modulation;
+mod pitch;
+mod pool;
+mod processing;
+mod random;
+mod reverb;
 mod sequence;
 mod step;
 mod subdivision;
 mod transporter;
 mod voice;
+mod worker;
 
+pub use capture::*;
+use click::*;
 pub use constants::*;
 use counter::*;
+use delay::*;
+pub use envelope::*;
+pub use filter::*;
+pub use granular::*;
+use input::*;
+pub use interpolation::*;
+pub use lfo::*;
+use master::*;
+pub use matrix::*;
+pub use modulation::*;
+pub use pitch::*;
+use pool::*;
+pub use processing::*;
+use random::*;
+use reverb::*;
 pub use sequence::*;
-use step::*;
+pub use step::*;
 pub use subdivision::*;
 use transporter::*;
 pub use triple_buffer::{triple_buffer, Input, Output};
+use worker::*;
 
 #[derive(Clone)]
 pub struct DrawData {
     pub positions: Vec<u8>,
     pub subdivisions: Vec<Subdivision>,
-    pub pitches: Vec<f32>,
+    pub pitches: Vec<Pitch>,
+    pub pitch_sequences: Vec<PitchSequence>,
     pub ranges: Vec<(u8, u8)>,
     pub dirs: Vec<PlayMode>,
     pub step_states: Vec<StepState>,
@@ -27,6 +64,65 @@ pub struct DrawData {
     pub gains: Vec<f32>,
     pub attacks: Vec<f32>,
     pub releases: Vec<f32>,
+    pub decays: Vec<f32>,
+    pub sustains: Vec<f32>,
+    pub gates: Vec<GateLength>,
+    pub curves: Vec<EnvCurve>,
+    pub interpolations: Vec<Interpolation>,
+    pub voice_modes: Vec<VoiceMode>,
+    pub grains: Vec<GrainParams>,
+    pub filter_modes: Vec<FilterMode>,
+    pub cutoffs: Vec<f32>,
+    pub resonances: Vec<f32>,
+    pub filter_envs: Vec<f32>,
+    pub delay_sends: Vec<f32>,
+    pub reverb_sends: Vec<f32>,
+    pub delay_subdivision: Subdivision,
+    pub delay_feedback: f32,
+    pub reverb_size: f32,
+    pub reverb_damping: f32,
+    pub master_gain: f32,
+    pub limiter_enabled: bool,
+    pub gain_reduction: f32,
+    pub voice_stealing: VoiceStealing,
+    pub dropped_voices: u32,
+    pub stolen_voices: u32,
+    pub active_voices: usize,
+    pub polyphony: usize,
+    pub monos: Vec<bool>,
+    pub reverses: Vec<bool>,
+    pub reverse_probabilities: Vec<f32>,
+    pub step_reverses: Vec<bool>,
+    pub choke_groups: Vec<Option<u8>>,
+    /// parameter values after modulation, indexed by `ModTarget::index`
+    pub modulated: Vec<[f32; MOD_TARGETS]>,
+    pub lfo_shapes: Vec<LfoShape>,
+    pub lfo_rates: Vec<LfoRate>,
+    pub lfo_depths: Vec<f32>,
+    pub lfo_targets: Vec<Option<(usize, ModTarget)>>,
+    pub lfo_values: Vec<f32>,
+    pub mod_sources: Vec<ModSource>,
+    pub mod_destinations: Vec<Option<(usize, ModTarget)>>,
+    pub mod_amounts: Vec<f32>,
+    pub mod_values: Vec<f32>,
+    pub input_gain: f32,
+    pub monitor_level: f32,
+    /// peak level of the input after the gain
+    pub input_level: f32,
+    pub input_clipped: bool,
+    pub record_source: RecordSource,
+    pub capture_length: CaptureLength,
+    pub record_arm: RecordArm,
+    pub record_threshold: f32,
+    pub record_mode: RecordMode,
+    pub overdub_feedback: f32,
+    pub processing: Processing,
+    pub edit_kind: EditKind,
+    pub edit_gain: f32,
+    pub edit_fade: f32,
+    pub crop: (f32, f32),
+    pub duplicate_target: usize,
+    pub record_lengths: Vec<RecordLength>,
 }
 
 impl DrawData {
@@ -36,13 +132,71 @@ impl DrawData {
             bpm: INIT_BPM,
             transporter: (0, 0, 0),
             subdivisions: vec![Subdivision::Quarter; SEQUENCES as usize],
-            pitches: vec![1.0; SEQUENCES as usize],
+            pitches: vec![Pitch::default(); SEQUENCES as usize],
+            pitch_sequences: vec![PitchSequence::default(); SEQUENCES as usize],
             ranges: vec![(0, 0); SEQUENCES as usize],
             dirs: vec![PlayMode::Forwards; SEQUENCES as usize],
             step_states: vec![StepState::Empty; STEP_NUM as usize],
             gains: vec![0.8; SEQUENCES as usize],
             attacks: vec![0.2; SEQUENCES as usize],
             releases: vec![0.8; SEQUENCES as usize],
+            decays: vec![0.1; SEQUENCES as usize],
+            sustains: vec![0.8; SEQUENCES as usize],
+            gates: vec![GateLength::Fraction(1.0); SEQUENCES as usize],
+            curves: vec![EnvCurve::Linear; SEQUENCES as usize],
+            interpolations: vec![Interpolation::Linear; SEQUENCES as usize],
+            voice_modes: vec![VoiceMode::OneShot; SEQUENCES as usize],
+            grains: vec![GrainParams::default(); SEQUENCES as usize],
+            filter_modes: vec![FilterMode::LowPass; SEQUENCES as usize],
+            cutoffs: vec![20000.0; SEQUENCES as usize],
+            resonances: vec![0.0; SEQUENCES as usize],
+            filter_envs: vec![0.0; SEQUENCES as usize],
+            delay_sends: vec![0.0; SEQUENCES as usize],
+            reverb_sends: vec![0.0; SEQUENCES as usize],
+            delay_subdivision: Subdivision::DottedEighth,
+            delay_feedback: 0.4,
+            reverb_size: 0.8,
+            reverb_damping: 0.5,
+            master_gain: 1.0,
+            limiter_enabled: true,
+            gain_reduction: 0.0,
+            voice_stealing: VoiceStealing::Drop,
+            dropped_voices: 0,
+            stolen_voices: 0,
+            active_voices: 0,
+            polyphony: MAX_POLYPHONY as usize,
+            monos: vec![false; SEQUENCES as usize],
+            reverses: vec![false; SEQUENCES as usize],
+            reverse_probabilities: vec![0.0; SEQUENCES as usize],
+            step_reverses: vec![false; STEP_NUM as usize],
+            choke_groups: vec![None; STEP_NUM as usize],
+            modulated: vec![[0.0; MOD_TARGETS]; SEQUENCES as usize],
+            lfo_shapes: vec![LfoShape::Sine; LFO_NUM as usize],
+            lfo_rates: vec![LfoRate::Sync(Subdivision::Quarter); LFO_NUM as usize],
+            lfo_depths: vec![0.5; LFO_NUM as usize],
+            lfo_targets: vec![None; LFO_NUM as usize],
+            lfo_values: vec![0.0; LFO_NUM as usize],
+            mod_sources: vec![ModSource::Lfo(0); MOD_SLOTS as usize],
+            mod_destinations: vec![None; MOD_SLOTS as usize],
+            mod_amounts: vec![0.5; MOD_SLOTS as usize],
+            mod_values: vec![0.0; MOD_SLOTS as usize],
+            input_gain: 1.0,
+            monitor_level: 0.0,
+            input_level: 0.0,
+            input_clipped: false,
+            record_source: RecordSource::Input,
+            capture_length: CaptureLength::Buffer,
+            record_arm: RecordArm::Immediate,
+            record_threshold: 0.1,
+            record_mode: RecordMode::Replace,
+            overdub_feedback: 0.8,
+            processing: Processing::default(),
+            edit_kind: EditKind::Reverse,
+            edit_gain: 3.0,
+            edit_fade: 0.05,
+            crop: (0.0, 1.0),
+            duplicate_target: 0,
+            record_lengths: vec![RecordLength::Seconds(AUDIO_BUFFER_SIZE_SEC); STEP_NUM as usize],
         }
     }
 }
@@ -52,12 +206,44 @@ pub struct Sequencer {
     sequences: Vec<Sequence>,
     draw_data: Input<DrawData>,
     steps: Vec<Step>,
+    buses: Vec<f32>,
+    delay: Delay,
+    reverb: Reverb,
+    master: MasterBus,
+    input: InputSection,
+    record_source: RecordSource,
+    history: InputHistory,
+    capture_length: CaptureLength,
+    record_arm: RecordArm,
+    /// input level that starts a threshold armed recording
+    record_threshold: f32,
+    record_mode: RecordMode,
+    /// how much of the existing recording is kept on each overdub pass
+    overdub_feedback: f32,
+    /// applied to recordings once they are finished
+    processing: Processing,
+    edit_kind: EditKind,
+    /// in dB
+    edit_gain: f32,
+    /// length of the fade edits in seconds
+    edit_fade: f32,
+    /// part of the recording a crop keeps, 0.0 - 1.0
+    crop: (f32, f32),
+    /// step a duplicate edit copies to
+    duplicate_target: usize,
+    worker: Worker,
+    click: Click,
+    voice_stealing: VoiceStealing,
+    voices: VoicePool,
+    lfos: Vec<Lfo>,
+    matrix: ModMatrix,
     transporter: Transporter,
 }
 
 impl Sequencer {
     pub fn new(sample_rate: f32) -> (Self, Output<DrawData>) {
         let (buf_input, buf_output) = triple_buffer(&DrawData::new());
+        init_interpolation();
         let bpm = INIT_BPM;
         (
             Sequencer {
@@ -69,25 +255,28 @@ impl Sequencer {
                         sample_rate,
                         bpm,
                         Subdivision::Quarter,
-                        1.0,
+                        Pitch::default(),
                         (0, 4),
                         PlayMode::Forwards,
+                        1,
                     ));
                     sequences.push(Sequence::new(
                         sample_rate,
                         bpm,
                         Subdivision::Eighth,
-                        1.0,
+                        Pitch::default(),
                         (3, 6),
                         PlayMode::Backwards,
+                        2,
                     ));
                     sequences.push(Sequence::new(
                         sample_rate,
                         bpm,
                         Subdivision::Sixteenth,
-                        1.0,
+                        Pitch::default(),
                         (4, 7),
                         PlayMode::BackAndForth(0),
+                        3,
                     ));
                     sequences
                 },
@@ -98,13 +287,195 @@ impl Sequencer {
                     }
                     steps
                 },
+                buses: vec![0.0; SEQUENCES as usize],
+                delay: Delay::new(sample_rate),
+                reverb: Reverb::new(sample_rate),
+                master: MasterBus::new(sample_rate),
+                input: InputSection::new(sample_rate),
+                record_source: RecordSource::Input,
+                history: InputHistory::new((sample_rate * AUDIO_BUFFER_SIZE_SEC) as usize),
+                capture_length: CaptureLength::Buffer,
+                record_arm: RecordArm::Immediate,
+                record_threshold: 0.1,
+                record_mode: RecordMode::Replace,
+                overdub_feedback: 0.8,
+                processing: Processing::default(),
+                edit_kind: EditKind::Reverse,
+                edit_gain: 3.0,
+                edit_fade: 0.05,
+                crop: (0.0, 1.0),
+                duplicate_target: 0,
+                worker: Worker::new(sample_rate),
+                click: Click::new(sample_rate),
+                voice_stealing: VoiceStealing::Drop,
+                voices: VoicePool::new(sample_rate),
+                lfos: (0..LFO_NUM)
+                    .map(|i| Lfo::new(sample_rate, i as u32 + 1))
+                    .collect(),
+                matrix: ModMatrix::new(sample_rate, MOD_SLOTS as usize),
                 transporter: Transporter::new(sample_rate),
             },
             buf_output,
         )
     }
 
+    /// Renders a single sample and publishes the draw data, use
+    /// `render_block` where the audio comes in blocks
     pub fn render(&mut self, sample: &mut f32) {
+        *sample = self.process(*sample);
+        self.exchange_jobs();
+        self.publish();
+    }
+
+    /// Renders a block of audio, the draw data is published once per block
+    pub fn render_block(&mut self, input: &[f32], output: &mut [f32]) {
+        for (input, output) in input.iter().zip(output.iter_mut()) {
+            *output = self.process(*input);
+        }
+        self.exchange_jobs();
+        self.publish();
+    }
+
+    fn process(&mut self, sample: f32) -> f32 {
+        let recording_input = self
+            .steps
+            .iter()
+            .any(|step| step.state == StepState::Recording && step.source == RecordSource::Input);
+        let input = self.input.process(sample, recording_input);
+        self.history.push(input);
+
+        let mut output = 0.0;
+
+        for sequence in self.sequences.iter_mut() {
+            sequence.modulation.clear();
+        }
+        for lfo in self.lfos.iter_mut() {
+            let value = lfo.update(self.bpm);
+            if let Some((target_idx, target)) = lfo.target {
+                if let Some(sequence) = self.sequences.get_mut(target_idx) {
+                    sequence.modulation.add(target, value * lfo.depth);
+                }
+            }
+        }
+
+        self.matrix.follow(input);
+        let transport_phase = self.transporter.phase();
+        for slot in self.matrix.slots.iter() {
+            let value =
+                self.matrix
+                    .source_value(slot.source, &self.lfos, &self.sequences, transport_phase);
+            if let Some((target_idx, target)) = slot.destination {
+                if let Some(sequence) = self.sequences.get_mut(target_idx) {
+                    sequence.modulation.add(target, value * slot.amount);
+                }
+            }
+        }
+
+        let apply = self.transporter.update();
+        for step in self.steps.iter_mut() {
+            step.check_arm(
+                self.transporter.beat,
+                self.transporter.downbeat,
+                self.input.level,
+                self.record_threshold,
+            );
+        }
+        if self.transporter.downbeat {
+            self.history.downbeat();
+        }
+        if self.transporter.beat && self.steps.iter().any(|step| step.is_counting_in()) {
+            self.click.trigger(self.transporter.downbeat);
+        }
+        for (i, sequence) in self.sequences.iter_mut().enumerate() {
+            if let Some(trigger) = sequence.update(apply, self.bpm) {
+                let step_idx = trigger.step as usize;
+                let choke_group = self.steps[step_idx].choke_group;
+                if sequence.mono {
+                    self.voices.choke_sequence(i);
+                }
+                for (j, step) in self.steps.iter().enumerate() {
+                    if choke_group.is_some() && step.choke_group == choke_group {
+                        self.voices.choke_step(j);
+                    }
+                }
+                if let Some(trigger) = self.steps[step_idx].trigger(&trigger) {
+                    self.voices.play(i, &trigger, self.voice_stealing);
+                }
+                self.matrix.retrigger(i);
+            }
+        }
+
+        self.buses.fill(0.0);
+        self.voices.render(&self.steps, &mut self.buses);
+        for (i, step) in self.steps.iter_mut().enumerate() {
+            if step.take_choke() {
+                self.voices.choke_step(i);
+            }
+            if step.has_pending_edit() {
+                if self.voices.is_playing_step(i) {
+                    self.voices.choke_step(i);
+                } else {
+                    step.apply_pending_edit();
+                }
+            }
+        }
+
+        let mut delay_input = 0.0;
+        let mut reverb_input = 0.0;
+        for (sequence, bus) in self.sequences.iter_mut().zip(self.buses.iter_mut()) {
+            *bus = sequence.process(*bus);
+            delay_input += *bus * sequence.delay_send;
+            reverb_input += *bus * sequence.reverb_send;
+            output += *bus;
+        }
+
+        output += self.delay.process(delay_input);
+        output += self.reverb.process(reverb_input);
+
+        output += input * self.input.monitor;
+        output += self.click.process();
+        let master = self.master.process(output);
+
+        // recording last, so the outputs of this sample can be resampled
+        for step in self.steps.iter_mut() {
+            if step.state == StepState::Recording {
+                let source = match step.source {
+                    RecordSource::Input => input,
+                    RecordSource::Master => master,
+                    RecordSource::Sequence(idx) => self.buses.get(idx).copied().unwrap_or(0.0),
+                };
+                step.record(source, self.overdub_feedback);
+            }
+        }
+
+        master
+    }
+
+    /// Hands finished recordings to the worker and swaps in what it sent back
+    fn exchange_jobs(&mut self) {
+        for i in 0..self.steps.len() {
+            if let Some(job) = self.steps[i].process_recording(i, self.processing) {
+                self.submit(job);
+            }
+        }
+        while let Some(job) = self.worker.try_recv() {
+            if let Some(step) = self.steps.get_mut(job.step) {
+                step.receive(job);
+            }
+        }
+    }
+
+    /// The spare of the target step is overwritten once the worker accepted the job,
+    /// a busy worker hands it back
+    fn submit(&mut self, job: Job) {
+        let target = job.step;
+        match self.worker.submit(job) {
+            Ok(()) => self.steps[target].forget_undo(),
+            Err(job) => self.steps[target].reclaim(job.buffer),
+        }
+    }
+
+    fn publish(&mut self) {
         let draw_data = self.draw_data.input_buffer();
         let positions = &mut draw_data.positions;
         let subdivisions = &mut draw_data.subdivisions;
@@ -117,27 +488,61 @@ impl Sequencer {
         let gains = &mut draw_data.gains;
         let attacks = &mut draw_data.attacks;
         let releases = &mut draw_data.releases;
+        let voice_modes = &mut draw_data.voice_modes;
+        let grains = &mut draw_data.grains;
+        let filter_modes = &mut draw_data.filter_modes;
+        let cutoffs = &mut draw_data.cutoffs;
+        let resonances = &mut draw_data.resonances;
+        let filter_envs = &mut draw_data.filter_envs;
+        let delay_sends = &mut draw_data.delay_sends;
+        let reverb_sends = &mut draw_data.reverb_sends;
 
-        for step in self.steps.iter_mut() {
-            if step.state == StepState::Recording {
-                step.record(*sample);
-            }
+        for (i, lfo) in self.lfos.iter().enumerate() {
+            draw_data.lfo_shapes[i] = lfo.shape;
+            draw_data.lfo_rates[i] = lfo.rate;
+            draw_data.lfo_depths[i] = lfo.depth;
+            draw_data.lfo_targets[i] = lfo.target;
+            draw_data.lfo_values[i] = lfo.value;
         }
 
-        let mut output = 0.0;
+        let transport_phase = self.transporter.phase();
+        for (i, slot) in self.matrix.slots.iter().enumerate() {
+            draw_data.mod_sources[i] = slot.source;
+            draw_data.mod_destinations[i] = slot.destination;
+            draw_data.mod_amounts[i] = slot.amount;
+            draw_data.mod_values[i] =
+                self.matrix
+                    .source_value(slot.source, &self.lfos, &self.sequences, transport_phase);
+        }
 
-        let apply = self.transporter.update();
-        for (i, sequence) in self.sequences.iter_mut().enumerate() {
-            if let Some((step, pitch, gain, attack, release)) = sequence.update(apply, self.bpm) {
-                self.steps[step as usize].play(pitch, gain, attack, release);
-            }
+        for (i, sequence) in self.sequences.iter().enumerate() {
             positions[i] = sequence.current_step;
             pitches[i] = sequence.pitch;
+            draw_data.pitch_sequences[i] = sequence.pitch_sequence;
             ranges[i] = sequence.play_range;
             dirs[i] = sequence.play_mode.clone();
             gains[i] = sequence.gain;
             attacks[i] = sequence.attack;
             releases[i] = sequence.release;
+            voice_modes[i] = sequence.voice_mode;
+            grains[i] = sequence.grain;
+            filter_modes[i] = sequence.filter.mode;
+            cutoffs[i] = sequence.filter.cutoff;
+            resonances[i] = sequence.filter.resonance;
+            filter_envs[i] = sequence.filter.env_amount;
+            delay_sends[i] = sequence.delay_send;
+            reverb_sends[i] = sequence.reverb_send;
+            draw_data.monos[i] = sequence.mono;
+            draw_data.reverses[i] = sequence.reverse;
+            draw_data.reverse_probabilities[i] = sequence.reverse_probability;
+            for target in ModTarget::ALL {
+                draw_data.modulated[i][target.index()] = sequence.live_value(target);
+            }
+            draw_data.decays[i] = sequence.decay;
+            draw_data.sustains[i] = sequence.sustain;
+            draw_data.gates[i] = sequence.gate;
+            draw_data.curves[i] = sequence.curve;
+            draw_data.interpolations[i] = sequence.interpolation;
 
             if let Some(subdivision) = sequence.next_subdivision {
                 subdivisions[i] = subdivision;
@@ -148,7 +553,15 @@ impl Sequencer {
 
         for (i, step) in self.steps.iter().enumerate() {
             step_states[i] = step.state.clone();
+            draw_data.choke_groups[i] = step.choke_group;
+            draw_data.step_reverses[i] = step.reverse;
+            draw_data.record_lengths[i] = step.record_length;
         }
+        draw_data.dropped_voices = self.voices.dropped;
+        draw_data.stolen_voices = self.voices.stolen;
+        draw_data.active_voices = self.voices.active_voices();
+        draw_data.polyphony = self.voices.polyphony;
+        draw_data.voice_stealing = self.voice_stealing;
 
         *transporter = (
             self.transporter.bar,
@@ -156,22 +569,161 @@ impl Sequencer {
             self.transporter.sixteenth,
         );
         *bpm = self.bpm;
+        draw_data.delay_subdivision = self.delay.subdivision;
+        draw_data.delay_feedback = self.delay.feedback;
+        draw_data.reverb_size = self.reverb.size;
+        draw_data.reverb_damping = self.reverb.damping;
+        draw_data.master_gain = self.master.gain;
+        draw_data.limiter_enabled = self.master.limiter_enabled;
+        draw_data.gain_reduction = self.master.gain_reduction();
+        draw_data.input_gain = self.input.gain;
+        draw_data.monitor_level = self.input.monitor;
+        draw_data.input_level = self.input.level;
+        draw_data.input_clipped = self.input.clipped;
+        draw_data.record_source = self.record_source;
+        draw_data.capture_length = self.capture_length;
+        draw_data.record_arm = self.record_arm;
+        draw_data.record_threshold = self.record_threshold;
+        draw_data.record_mode = self.record_mode;
+        draw_data.overdub_feedback = self.overdub_feedback;
+        draw_data.processing = self.processing;
+        draw_data.edit_kind = self.edit_kind;
+        draw_data.edit_gain = self.edit_gain;
+        draw_data.edit_fade = self.edit_fade;
+        draw_data.crop = self.crop;
+        draw_data.duplicate_target = self.duplicate_target;
         self.draw_data.publish();
+    }
 
-        for step in self.steps.iter_mut() {
-            output += step.render();
+    pub fn record(&mut self, step_idx: usize) {
+        if let Some(step) = self.steps.get_mut(step_idx) {
+            step.arm(self.record_source, self.record_arm, self.record_mode);
+            self.input.clipped = false;
+        }
+    }
+
+    /// Copies the recent input into a step, see `set_capture_length`
+    pub fn capture(&mut self, step_idx: usize) {
+        if let Some(step) = self.steps.get_mut(step_idx) {
+            step.capture(&self.history, self.capture_length);
         }
+    }
 
-        *sample = output;
+    /// When recordings started from now on begin
+    pub fn set_record_arm(&mut self, arm: RecordArm) {
+        self.record_arm = arm;
     }
 
-    pub fn record(&mut self, step_idx: usize) {
+    pub fn set_record_threshold(&mut self, threshold: f32) {
+        self.record_threshold = threshold;
+    }
+
+    /// Whether recordings started from now on replace or overdub a recorded step
+    pub fn set_record_mode(&mut self, mode: RecordMode) {
+        self.record_mode = mode;
+    }
+
+    pub fn set_overdub_feedback(&mut self, feedback: f32) {
+        self.overdub_feedback = feedback;
+    }
+
+    /// Applies the selected edit to a step, off the audio thread
+    pub fn edit(&mut self, step_idx: usize) {
+        let edit = match self.edit_kind {
+            EditKind::Reverse => BufferEdit::Reverse,
+            EditKind::Normalise => BufferEdit::Normalise(self.processing.target_peak),
+            EditKind::Gain => BufferEdit::Gain(self.edit_gain),
+            EditKind::FadeIn => BufferEdit::FadeIn(self.edit_fade),
+            EditKind::FadeOut => BufferEdit::FadeOut(self.edit_fade),
+            EditKind::Crop => BufferEdit::Crop(self.crop.0, self.crop.1),
+            EditKind::Duplicate => return self.duplicate(step_idx, self.duplicate_target),
+        };
+        self.start_edit(step_idx, step_idx, edit);
+    }
+
+    fn duplicate(&mut self, from: usize, to: usize) {
+        if from != to {
+            self.start_edit(from, to, BufferEdit::Copy);
+        }
+    }
+
+    /// Shares the recording of `from` with the worker, to be edited into the
+    /// spare of `to`, `from` plays on until the result is swapped in
+    fn start_edit(&mut self, from: usize, to: usize, edit: BufferEdit) {
+        let job = match self.steps.get(from).and_then(|step| step.share()) {
+            Some((source, length)) => self
+                .steps
+                .get_mut(to)
+                .and_then(|step| step.job(to, source, length, edit)),
+            None => None,
+        };
+        if let Some(job) = job {
+            self.submit(job);
+        }
+    }
+
+    /// Undoes the last edit of a step, or redoes it after an undo
+    pub fn undo(&mut self, step_idx: usize) {
         if let Some(step) = self.steps.get_mut(step_idx) {
-            step.state = StepState::Recording;
-            step.record_head = 0;
+            step.undo();
         }
     }
 
+    pub fn set_edit_kind(&mut self, kind: EditKind) {
+        self.edit_kind = kind;
+    }
+
+    pub fn set_edit_gain(&mut self, gain: f32) {
+        self.edit_gain = gain;
+    }
+
+    pub fn set_edit_fade(&mut self, fade: f32) {
+        self.edit_fade = fade;
+    }
+
+    pub fn set_crop(&mut self, crop: (f32, f32)) {
+        self.crop = crop;
+    }
+
+    pub fn set_duplicate_target(&mut self, step_idx: usize) {
+        self.duplicate_target = step_idx;
+    }
+
+    pub fn toggle_processing(&mut self) {
+        self.processing.enabled = !self.processing.enabled;
+    }
+
+    pub fn set_trim_threshold(&mut self, threshold: f32) {
+        self.processing.trim_threshold = threshold;
+    }
+
+    pub fn set_target_peak(&mut self, peak: f32) {
+        self.processing.target_peak = peak;
+    }
+
+    pub fn set_record_length(&mut self, step_idx: usize, length: RecordLength) {
+        if let Some(step) = self.steps.get_mut(step_idx) {
+            step.record_length = length;
+        }
+    }
+
+    pub fn set_capture_length(&mut self, length: CaptureLength) {
+        self.capture_length = length;
+    }
+
+    /// Source of the recordings started from now on
+    pub fn set_record_source(&mut self, source: RecordSource) {
+        self.record_source = source;
+    }
+
+    pub fn set_input_gain(&mut self, gain: f32) {
+        self.input.gain = gain;
+    }
+
+    pub fn set_monitor_level(&mut self, level: f32) {
+        self.input.monitor = level;
+    }
+
     pub fn erase(&mut self, step_idx: usize) {
         if let Some(step) = self.steps.get_mut(step_idx) {
             step.erase();
@@ -179,9 +731,34 @@ impl Sequencer {
     }
 
     pub fn set_pitch(&mut self, idx: usize, semitone: i8) {
-        let pitch = 2.0f32.powf(semitone as f32 / 12.0);
         if let Some(sequence) = self.sequences.get_mut(idx) {
-            sequence.pitch = pitch;
+            sequence.pitch.semitones = semitone.clamp(-MAX_SEMITONES, MAX_SEMITONES);
+        }
+    }
+
+    pub fn set_fine_tune(&mut self, idx: usize, cents: i8) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.pitch.cents = cents.clamp(-MAX_CENTS, MAX_CENTS);
+        }
+    }
+
+    pub fn set_scale(&mut self, idx: usize, scale: Scale) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.pitch.scale = scale;
+        }
+    }
+
+    pub fn set_pitch_sequence_length(&mut self, idx: usize, length: u8) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.pitch_sequence.set_length(length);
+        }
+    }
+
+    pub fn set_pitch_sequence_step(&mut self, idx: usize, step: usize, semitone: i8) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            if let Some(offset) = sequence.pitch_sequence.offsets.get_mut(step) {
+                *offset = semitone.clamp(-MAX_SEMITONES, MAX_SEMITONES);
+            }
         }
     }
 
@@ -215,12 +792,226 @@ impl Sequencer {
         }
     }
 
+    pub fn set_decay(&mut self, val: f32, sequence: usize) {
+        if let Some(sequence) = self.sequences.get_mut(sequence) {
+            sequence.decay = val;
+        }
+    }
+
+    pub fn set_sustain(&mut self, val: f32, sequence: usize) {
+        if let Some(sequence) = self.sequences.get_mut(sequence) {
+            sequence.sustain = val;
+        }
+    }
+
+    pub fn set_gate(&mut self, val: GateLength, sequence: usize) {
+        if let Some(sequence) = self.sequences.get_mut(sequence) {
+            sequence.gate = val;
+        }
+    }
+
+    pub fn set_curve(&mut self, val: EnvCurve, sequence: usize) {
+        if let Some(sequence) = self.sequences.get_mut(sequence) {
+            sequence.curve = val;
+        }
+    }
+
+    pub fn set_interpolation(&mut self, val: Interpolation, sequence: usize) {
+        if let Some(sequence) = self.sequences.get_mut(sequence) {
+            sequence.interpolation = val;
+        }
+    }
+
     pub fn set_gain(&mut self, val: f32, sequence: usize) {
         if let Some(sequence) = self.sequences.get_mut(sequence) {
             sequence.gain = val
         }
     }
 
+    pub fn set_voice_mode(&mut self, idx: usize, mode: VoiceMode) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.voice_mode = mode;
+        }
+    }
+
+    pub fn set_grain_size(&mut self, idx: usize, size: f32) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.grain.size = size;
+        }
+    }
+
+    pub fn set_grain_density(&mut self, idx: usize, density: f32) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.grain.density = density;
+        }
+    }
+
+    pub fn set_grain_position(&mut self, idx: usize, position: f32) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.grain.position = position;
+        }
+    }
+
+    pub fn set_position_jitter(&mut self, idx: usize, jitter: f32) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.grain.position_jitter = jitter;
+        }
+    }
+
+    pub fn set_pitch_jitter(&mut self, idx: usize, jitter: f32) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.grain.pitch_jitter = jitter;
+        }
+    }
+
+    pub fn set_grain_window(&mut self, idx: usize, window: GrainWindow) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.grain.window = window;
+        }
+    }
+
+    pub fn set_filter_mode(&mut self, idx: usize, mode: FilterMode) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.filter.mode = mode;
+        }
+    }
+
+    pub fn set_cutoff(&mut self, idx: usize, cutoff: f32) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.filter.cutoff = cutoff;
+        }
+    }
+
+    pub fn set_resonance(&mut self, idx: usize, resonance: f32) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.filter.resonance = resonance;
+        }
+    }
+
+    pub fn set_filter_env(&mut self, idx: usize, amount: f32) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.filter.env_amount = amount;
+        }
+    }
+
+    pub fn set_delay_send(&mut self, idx: usize, send: f32) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.delay_send = send;
+        }
+    }
+
+    pub fn set_reverb_send(&mut self, idx: usize, send: f32) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.reverb_send = send;
+        }
+    }
+
+    pub fn set_delay_subdivision(&mut self, subdivision: Subdivision) {
+        self.delay.set_subdivision(subdivision, self.bpm);
+    }
+
+    pub fn set_delay_feedback(&mut self, feedback: f32) {
+        self.delay.feedback = feedback;
+    }
+
+    pub fn set_reverb_size(&mut self, size: f32) {
+        self.reverb.size = size;
+    }
+
+    pub fn set_reverb_damping(&mut self, damping: f32) {
+        self.reverb.damping = damping;
+    }
+
+    pub fn set_master_gain(&mut self, gain: f32) {
+        self.master.gain = gain;
+    }
+
+    pub fn toggle_limiter(&mut self) {
+        self.master.limiter_enabled = !self.master.limiter_enabled;
+    }
+
+    pub fn set_mono(&mut self, idx: usize, mono: bool) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.mono = mono;
+        }
+    }
+
+    pub fn set_reverse(&mut self, idx: usize, reverse: bool) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.reverse = reverse;
+        }
+    }
+
+    pub fn set_reverse_probability(&mut self, idx: usize, probability: f32) {
+        if let Some(sequence) = self.sequences.get_mut(idx) {
+            sequence.reverse_probability = probability;
+        }
+    }
+
+    /// A reversed step plays backwards whatever the sequence says
+    pub fn set_step_reverse(&mut self, step_idx: usize, reverse: bool) {
+        if let Some(step) = self.steps.get_mut(step_idx) {
+            step.reverse = reverse;
+        }
+    }
+
+    pub fn set_choke_group(&mut self, step_idx: usize, group: Option<u8>) {
+        if let Some(step) = self.steps.get_mut(step_idx) {
+            step.choke_group = group;
+        }
+    }
+
+    /// Voices that may play at once, up to `MAX_POLYPHONY`
+    pub fn set_polyphony(&mut self, polyphony: usize) {
+        self.voices.polyphony = polyphony.clamp(1, MAX_POLYPHONY as usize);
+    }
+
+    pub fn set_voice_stealing(&mut self, stealing: VoiceStealing) {
+        self.voice_stealing = stealing;
+    }
+
+    pub fn set_lfo_shape(&mut self, idx: usize, shape: LfoShape) {
+        if let Some(lfo) = self.lfos.get_mut(idx) {
+            lfo.shape = shape;
+        }
+    }
+
+    pub fn set_lfo_rate(&mut self, idx: usize, rate: LfoRate) {
+        if let Some(lfo) = self.lfos.get_mut(idx) {
+            lfo.rate = rate;
+        }
+    }
+
+    pub fn set_lfo_depth(&mut self, idx: usize, depth: f32) {
+        if let Some(lfo) = self.lfos.get_mut(idx) {
+            lfo.depth = depth;
+        }
+    }
+
+    pub fn set_lfo_target(&mut self, idx: usize, target: Option<(usize, ModTarget)>) {
+        if let Some(lfo) = self.lfos.get_mut(idx) {
+            lfo.target = target;
+        }
+    }
+
+    pub fn set_mod_source(&mut self, slot: usize, source: ModSource) {
+        if let Some(slot) = self.matrix.slots.get_mut(slot) {
+            slot.source = source;
+        }
+    }
+
+    pub fn set_mod_destination(&mut self, slot: usize, destination: Option<(usize, ModTarget)>) {
+        if let Some(slot) = self.matrix.slots.get_mut(slot) {
+            slot.destination = destination;
+        }
+    }
+
+    pub fn set_mod_amount(&mut self, slot: usize, amount: f32) {
+        if let Some(slot) = self.matrix.slots.get_mut(slot) {
+            slot.amount = amount;
+        }
+    }
+
     pub fn toggle(&mut self, idx: usize) {
         if let Some(sequence) = self.sequences.get_mut(idx) {
             sequence.toggle();
@@ -230,9 +1021,13 @@ impl Sequencer {
     pub fn set_bpm(&mut self, bpm: f32) {
         self.bpm = bpm;
         self.transporter.set_bpm(bpm);
+        self.delay.set_bpm(bpm);
         for sequence in self.sequences.iter_mut() {
             sequence.set_bpm(bpm);
         }
+        for step in self.steps.iter_mut() {
+            step.set_bpm(bpm);
+        }
     }
 
     pub fn set_subdivision(&mut self, index: usize, subdivision: Subdivision) {
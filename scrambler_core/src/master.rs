@@ -0,0 +1,87 @@
+/// Lookahead of the limiter in seconds
+const LOOKAHEAD_SEC: f32 = 0.005;
+const RELEASE_SEC: f32 = 0.1;
+const CEILING: f32 = 0.98;
+/// Level above which the soft clipper starts to bend the signal, the
+/// limiter keeps the output below it
+const CLIP_KNEE: f32 = CEILING;
+
+/// Output stage with a gain, a lookahead peak limiter and a soft clipper
+/// catching what gets past the limiter
+pub struct MasterBus {
+    pub gain: f32,
+    pub limiter_enabled: bool,
+    lookahead: Vec<f32>,
+    lookahead_head: usize,
+    hold: f32,
+    hold_timer: usize,
+    env: f32,
+    attack_coef: f32,
+    release_coef: f32,
+}
+
+impl MasterBus {
+    pub fn new(sample_rate: f32) -> Self {
+        let lookahead = (LOOKAHEAD_SEC * sample_rate) as usize;
+        MasterBus {
+            gain: 1.0,
+            limiter_enabled: true,
+            lookahead: vec![0.0; lookahead],
+            lookahead_head: 0,
+            hold: 1.0,
+            hold_timer: 0,
+            env: 1.0,
+            attack_coef: 1.0 - (-5.0 / lookahead as f32).exp(),
+            release_coef: 1.0 - (-1.0 / (RELEASE_SEC * sample_rate)).exp(),
+        }
+    }
+
+    /// Current gain reduction of the limiter in dB
+    pub fn gain_reduction(&self) -> f32 {
+        -20.0 * self.env.log10()
+    }
+
+    fn soft_clip(sample: f32) -> f32 {
+        let level = sample.abs();
+        if level <= CLIP_KNEE {
+            sample
+        } else {
+            let range = 1.0 - CLIP_KNEE;
+            sample.signum() * (CLIP_KNEE + range * ((level - CLIP_KNEE) / range).tanh())
+        }
+    }
+
+    pub fn process(&mut self, sample: f32) -> f32 {
+        let sample = sample * self.gain;
+        // a disabled limiter releases to unity but keeps the delay, so
+        // toggling it neither jumps in time nor replays old samples
+        let required = if self.limiter_enabled && sample.abs() > CEILING {
+            CEILING / sample.abs()
+        } else {
+            1.0
+        };
+
+        // hold the lowest gain for the whole lookahead, so the
+        // delayed peak is reduced by the time it reaches the output
+        if required <= self.hold {
+            self.hold = required;
+            self.hold_timer = self.lookahead.len();
+        } else if self.hold_timer > 0 {
+            self.hold_timer -= 1;
+        } else {
+            self.hold = required;
+        }
+
+        if self.hold < self.env {
+            self.env += (self.hold - self.env) * self.attack_coef;
+        } else {
+            self.env += (self.hold - self.env) * self.release_coef;
+        }
+
+        let delayed = self.lookahead[self.lookahead_head];
+        self.lookahead[self.lookahead_head] = sample;
+        self.lookahead_head = (self.lookahead_head + 1) % self.lookahead.len();
+
+        Self::soft_clip(delayed * self.env)
+    }
+}
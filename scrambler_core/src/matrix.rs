@@ -0,0 +1,217 @@
+use crate::{Lfo, ModTarget, Random, Sequence, LFO_NUM, SEQUENCES};
+
+/// Follower attack and release in seconds
+const FOLLOWER_ATTACK: f32 = 0.005;
+const FOLLOWER_RELEASE: f32 = 0.15;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModSource {
+    Lfo(usize),
+    /// envelope of the audio input
+    Input,
+    /// position of a sequence within its play range
+    Position(usize),
+    /// new random value on every trigger of a sequence
+    Random(usize),
+    /// position within the current bar
+    Transport,
+}
+
+impl ModSource {
+    const COUNT: usize = LFO_NUM as usize + 2 * SEQUENCES as usize + 2;
+
+    pub fn get_symbol(&self) -> String {
+        match self {
+            ModSource::Lfo(idx) => format!("lfo{}", idx + 1),
+            ModSource::Input => String::from("input"),
+            ModSource::Position(idx) => format!("pos{}", idx + 1),
+            ModSource::Random(idx) => format!("rnd{}", idx + 1),
+            ModSource::Transport => String::from("bar"),
+        }
+    }
+
+    fn index(&self) -> usize {
+        let lfos = LFO_NUM as usize;
+        let sequences = SEQUENCES as usize;
+        match self {
+            ModSource::Lfo(idx) => *idx,
+            ModSource::Input => lfos,
+            ModSource::Position(idx) => lfos + 1 + idx,
+            ModSource::Random(idx) => lfos + 1 + sequences + idx,
+            ModSource::Transport => lfos + 1 + 2 * sequences,
+        }
+    }
+
+    fn from_index(index: usize) -> Self {
+        let lfos = LFO_NUM as usize;
+        let sequences = SEQUENCES as usize;
+        match index {
+            idx if idx < lfos => ModSource::Lfo(idx),
+            idx if idx == lfos => ModSource::Input,
+            idx if idx <= lfos + sequences => ModSource::Position(idx - lfos - 1),
+            idx if idx <= lfos + 2 * sequences => ModSource::Random(idx - lfos - 1 - sequences),
+            _ => ModSource::Transport,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        ModSource::from_index((self.index() + 1) % ModSource::COUNT)
+    }
+
+    pub fn prev(&self) -> Self {
+        ModSource::from_index((self.index() + ModSource::COUNT - 1) % ModSource::COUNT)
+    }
+
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        (0..ModSource::COUNT)
+            .map(ModSource::from_index)
+            .find(|source| source.get_symbol() == symbol)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModSlot {
+    pub source: ModSource,
+    /// sequence and parameter this slot modulates
+    pub destination: Option<(usize, ModTarget)>,
+    /// bipolar, -1.0 - 1.0
+    pub amount: f32,
+}
+
+impl ModSlot {
+    /// The slot as a line of a saved matrix, like `lfo1 2:cut 0.5` or `input off -0.3`
+    pub fn to_line(&self) -> String {
+        let destination = match self.destination {
+            Some((sequence, target)) => format!("{}:{}", sequence + 1, target.get_symbol()),
+            None => String::from("off"),
+        };
+        format!(
+            "{} {} {}",
+            self.source.get_symbol(),
+            destination,
+            self.amount
+        )
+    }
+
+    /// Reads a line written by `to_line`
+    pub fn from_line(line: &str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        let source = ModSource::from_symbol(fields.next()?)?;
+        let destination = match fields.next()? {
+            "off" => None,
+            destination => {
+                let (sequence, target) = destination.split_once(':')?;
+                let sequence = sequence.parse::<usize>().ok()?.checked_sub(1)?;
+                let target = ModTarget::ALL
+                    .into_iter()
+                    .find(|candidate| candidate.get_symbol() == target)?;
+                if sequence >= SEQUENCES as usize {
+                    return None;
+                }
+                Some((sequence, target))
+            }
+        };
+        let amount = fields
+            .next()?
+            .parse::<f32>()
+            .ok()
+            .filter(|amount| amount.is_finite())?
+            .clamp(-1.0, 1.0);
+        Some(ModSlot {
+            source,
+            destination,
+            amount,
+        })
+    }
+}
+
+/// Peak follower with separate attack and release
+struct Follower {
+    value: f32,
+    attack_coef: f32,
+    release_coef: f32,
+}
+
+impl Follower {
+    fn new(sample_rate: f32) -> Self {
+        Follower {
+            value: 0.0,
+            attack_coef: (-1.0 / (FOLLOWER_ATTACK * sample_rate)).exp(),
+            release_coef: (-1.0 / (FOLLOWER_RELEASE * sample_rate)).exp(),
+        }
+    }
+
+    fn process(&mut self, sample: f32) -> f32 {
+        let level = sample.abs();
+        let coef = if level > self.value {
+            self.attack_coef
+        } else {
+            self.release_coef
+        };
+        self.value = level + coef * (self.value - level);
+        self.value
+    }
+}
+
+/// Routes modulation sources to sequence parameters
+pub struct ModMatrix {
+    pub slots: Vec<ModSlot>,
+    follower: Follower,
+    randoms: Vec<f32>,
+    random: Random,
+}
+
+impl ModMatrix {
+    pub fn new(sample_rate: f32, slots: usize) -> Self {
+        let mut random = Random::new(0x5eed);
+        ModMatrix {
+            slots: vec![
+                ModSlot {
+                    source: ModSource::Lfo(0),
+                    destination: None,
+                    amount: 0.5,
+                };
+                slots
+            ],
+            follower: Follower::new(sample_rate),
+            randoms: (0..SEQUENCES).map(|_| random.bipolar()).collect(),
+            random,
+        }
+    }
+
+    /// Feeds the input envelope follower
+    pub fn follow(&mut self, input: f32) {
+        self.follower.process(input);
+    }
+
+    /// Rolls the random value the next trigger of `sequence` will see
+    pub fn retrigger(&mut self, sequence: usize) {
+        if let Some(value) = self.randoms.get_mut(sequence) {
+            *value = self.random.bipolar();
+        }
+    }
+
+    /// Current value of a source, lfos and randoms are bipolar, the rest 0.0 - 1.0
+    pub fn source_value(
+        &self,
+        source: ModSource,
+        lfos: &[Lfo],
+        sequences: &[Sequence],
+        transport_phase: f32,
+    ) -> f32 {
+        match source {
+            ModSource::Lfo(idx) => lfos.get(idx).map_or(0.0, |lfo| lfo.value),
+            ModSource::Input => self.follower.value,
+            ModSource::Position(idx) => sequences.get(idx).map_or(0.0, |sequence| {
+                let (start, end) = sequence.live_range();
+                if end > start {
+                    (sequence.current_step.clamp(start, end) - start) as f32 / (end - start) as f32
+                } else {
+                    0.0
+                }
+            }),
+            ModSource::Random(idx) => self.randoms.get(idx).copied().unwrap_or(0.0),
+            ModSource::Transport => transport_phase,
+        }
+    }
+}
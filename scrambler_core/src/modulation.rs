@@ -0,0 +1,94 @@
+pub const MOD_TARGETS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModTarget {
+    Pitch,
+    Gain,
+    Attack,
+    Release,
+    RangeStart,
+    RangeEnd,
+    Cutoff,
+    Decay,
+    Sustain,
+    GrainPosition,
+}
+
+impl ModTarget {
+    pub const ALL: [ModTarget; MOD_TARGETS] = [
+        ModTarget::Pitch,
+        ModTarget::Gain,
+        ModTarget::Attack,
+        ModTarget::Release,
+        ModTarget::RangeStart,
+        ModTarget::RangeEnd,
+        ModTarget::Cutoff,
+        ModTarget::Decay,
+        ModTarget::Sustain,
+        ModTarget::GrainPosition,
+    ];
+
+    pub fn get_symbol(&self) -> &str {
+        match self {
+            ModTarget::Pitch => "pitch",
+            ModTarget::Gain => "gain",
+            ModTarget::Attack => "att",
+            ModTarget::Release => "rel",
+            ModTarget::RangeStart => "start",
+            ModTarget::RangeEnd => "end",
+            ModTarget::Cutoff => "cut",
+            ModTarget::Decay => "dec",
+            ModTarget::Sustain => "sus",
+            ModTarget::GrainPosition => "pos",
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        match self {
+            ModTarget::Pitch => 0,
+            ModTarget::Gain => 1,
+            ModTarget::Attack => 2,
+            ModTarget::Release => 3,
+            ModTarget::RangeStart => 4,
+            ModTarget::RangeEnd => 5,
+            ModTarget::Cutoff => 6,
+            ModTarget::Decay => 7,
+            ModTarget::Sustain => 8,
+            ModTarget::GrainPosition => 9,
+        }
+    }
+
+    /// Offset at full modulation depth, in the unit of the target:
+    /// semitones, gain, seconds, steps, octaves or buffer fraction
+    fn range(&self) -> f32 {
+        match self {
+            ModTarget::Pitch => 12.0,
+            ModTarget::Gain | ModTarget::Sustain => 1.0,
+            ModTarget::Attack | ModTarget::Decay | ModTarget::Release => 1.0,
+            ModTarget::RangeStart | ModTarget::RangeEnd => 7.0,
+            ModTarget::Cutoff => 5.0,
+            ModTarget::GrainPosition => 1.0,
+        }
+    }
+}
+
+/// Sum of all modulation applied to a sequence during the current sample
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Modulation {
+    offsets: [f32; MOD_TARGETS],
+}
+
+impl Modulation {
+    pub fn clear(&mut self) {
+        self.offsets = [0.0; MOD_TARGETS];
+    }
+
+    /// Adds a bipolar amount between -1.0 and 1.0 to `target`
+    pub fn add(&mut self, target: ModTarget, amount: f32) {
+        self.offsets[target.index()] += amount * target.range();
+    }
+
+    pub fn get(&self, target: ModTarget) -> f32 {
+        self.offsets[target.index()]
+    }
+}
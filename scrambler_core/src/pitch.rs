@@ -0,0 +1,186 @@
+use crate::PITCH_STEPS;
+
+/// Transposition range in semitones, up and down
+pub const MAX_SEMITONES: i8 = 48;
+/// Fine tuning range in cents, up and down
+pub const MAX_CENTS: i8 = 50;
+/// Notes in an octave, one bit each in a scale mask
+pub const SCALE_NOTES: u8 = 12;
+
+const MAJOR: u16 = 0b1010_1011_0101;
+const MINOR: u16 = 0b0101_1010_1101;
+const PENTATONIC: u16 = 0b0010_1001_0101;
+/// whole tone, the first custom scale the tui offers
+const DEFAULT_CUSTOM: u16 = 0b0101_0101_0101;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scale {
+    /// no quantisation, modulation moves the pitch freely
+    Off,
+    Chromatic,
+    Major,
+    Minor,
+    Pentatonic,
+    /// bit `n` enables the note `n` semitones above the recorded pitch
+    Custom(u16),
+}
+
+impl Scale {
+    pub fn get_symbol(&self) -> &str {
+        match self {
+            Scale::Off => "off",
+            Scale::Chromatic => "chr",
+            Scale::Major => "maj",
+            Scale::Minor => "min",
+            Scale::Pentatonic => "pent",
+            Scale::Custom(_) => "cust",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Scale::Off => Scale::Chromatic,
+            Scale::Chromatic => Scale::Major,
+            Scale::Major => Scale::Minor,
+            Scale::Minor => Scale::Pentatonic,
+            Scale::Pentatonic => Scale::Custom(DEFAULT_CUSTOM),
+            Scale::Custom(_) => Scale::Off,
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        match self {
+            Scale::Off => Scale::Custom(DEFAULT_CUSTOM),
+            Scale::Chromatic => Scale::Off,
+            Scale::Major => Scale::Chromatic,
+            Scale::Minor => Scale::Major,
+            Scale::Pentatonic => Scale::Minor,
+            Scale::Custom(_) => Scale::Pentatonic,
+        }
+    }
+
+    /// Bit `n` is set when the note `n` semitones above the recorded pitch is in the scale
+    pub fn mask(&self) -> u16 {
+        match self {
+            Scale::Off | Scale::Chromatic => 0b1111_1111_1111,
+            Scale::Major => MAJOR,
+            Scale::Minor => MINOR,
+            Scale::Pentatonic => PENTATONIC,
+            Scale::Custom(mask) => *mask,
+        }
+    }
+
+    /// Custom scale with the notes of this one, `note` switched on or off
+    pub fn toggle_note(&self, note: u8) -> Self {
+        Scale::Custom(self.mask() ^ (1 << (note % SCALE_NOTES)))
+    }
+
+    fn contains(&self, note: i32) -> bool {
+        self.mask() & (1 << note.rem_euclid(12)) != 0
+    }
+
+    /// Snaps `note` in semitones to the nearest note of the scale
+    pub fn quantise(&self, note: f32) -> f32 {
+        if *self == Scale::Off || self.mask() & 0b1111_1111_1111 == 0 {
+            return note;
+        }
+        let nearest = note.round() as i32;
+        for distance in 0..=6 {
+            // prefer the note in the direction `note` was already leaning
+            let (first, second) = if note >= nearest as f32 {
+                (nearest + distance, nearest - distance)
+            } else {
+                (nearest - distance, nearest + distance)
+            };
+            if self.contains(first) {
+                return first as f32;
+            }
+            if self.contains(second) {
+                return second as f32;
+            }
+        }
+        note
+    }
+
+    /// Moves `note` by `offset` semitones onto the scale, keeping its fine tuning
+    pub fn transpose(&self, note: f32, offset: f32) -> f32 {
+        if *self == Scale::Off {
+            return note + offset;
+        }
+        let fine = note - note.round();
+        self.quantise(note.round() + offset) + fine
+    }
+}
+
+/// Transposition of a sequence relative to the recorded audio
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pitch {
+    pub semitones: i8,
+    pub cents: i8,
+    pub scale: Scale,
+}
+
+impl Default for Pitch {
+    fn default() -> Self {
+        Pitch {
+            semitones: 0,
+            cents: 0,
+            scale: Scale::Off,
+        }
+    }
+}
+
+impl Pitch {
+    /// Unmodulated pitch in semitones, including the fine tuning
+    pub fn note(&self) -> f32 {
+        self.semitones as f32 + self.cents as f32 / 100.0
+    }
+
+    /// Playback speed after `offset` semitones of modulation
+    pub fn ratio(&self, offset: f32) -> f32 {
+        2.0f32.powf(self.scale.transpose(self.note(), offset) / 12.0)
+    }
+}
+
+/// Semitone offsets stepped through on every trigger, independent of the play range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PitchSequence {
+    pub offsets: [i8; PITCH_STEPS as usize],
+    pub length: u8,
+    /// index of the offset the next trigger plays
+    pub position: u8,
+    /// offset of the last trigger
+    pub current: i8,
+}
+
+impl Default for PitchSequence {
+    fn default() -> Self {
+        PitchSequence {
+            offsets: [0; PITCH_STEPS as usize],
+            length: 1,
+            position: 0,
+            current: 0,
+        }
+    }
+}
+
+impl PitchSequence {
+    /// Moves on to the next offset and returns it
+    pub fn advance(&mut self) -> i8 {
+        if self.position >= self.length {
+            self.position = 0;
+        }
+        self.current = self.offsets[self.position as usize];
+        self.position = (self.position + 1) % self.length;
+        self.current
+    }
+
+    pub fn reset(&mut self) {
+        self.position = 0;
+        self.current = 0;
+    }
+
+    pub fn set_length(&mut self, length: u8) {
+        self.length = length.clamp(1, PITCH_STEPS);
+    }
+}
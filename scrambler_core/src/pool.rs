@@ -0,0 +1,121 @@
+use crate::{voice::Voice, Step, Trigger, VoiceStealing, MAX_POLYPHONY};
+
+/// Voices shared by all steps, only the active ones are rendered
+pub struct VoicePool {
+    voices: Vec<Voice>,
+    /// indices of the playing voices
+    active: Vec<usize>,
+    /// indices of the voices that are free to start
+    free: Vec<usize>,
+    /// voices that may play at once, at most `MAX_POLYPHONY`
+    pub polyphony: usize,
+    /// triggers lost because every voice was busy
+    pub dropped: u32,
+    /// voices taken over by a new trigger
+    pub stolen: u32,
+}
+
+impl VoicePool {
+    pub fn new(sample_rate: f32) -> Self {
+        VoicePool {
+            voices: (0..MAX_POLYPHONY)
+                .map(|i| Voice::new(sample_rate, i as u32 + 1))
+                .collect(),
+            active: Vec::with_capacity(MAX_POLYPHONY as usize),
+            free: (0..MAX_POLYPHONY as usize).rev().collect(),
+            polyphony: MAX_POLYPHONY as usize,
+            dropped: 0,
+            stolen: 0,
+        }
+    }
+
+    pub fn active_voices(&self) -> usize {
+        self.active.len()
+    }
+
+    pub fn play(&mut self, sequence: usize, trigger: &Trigger, stealing: VoiceStealing) {
+        if self.active.len() < self.polyphony {
+            if let Some(idx) = self.free.pop() {
+                self.voices[idx].start(sequence, trigger);
+                self.active.push(idx);
+                return;
+            }
+        }
+
+        let voices = &self.voices;
+        let candidates = self
+            .active
+            .iter()
+            .copied()
+            .filter(|idx| !voices[*idx].is_fading());
+        let idx = match stealing {
+            VoiceStealing::Drop => None,
+            VoiceStealing::Oldest | VoiceStealing::FadeOldest => {
+                candidates.max_by_key(|idx| voices[*idx].age)
+            }
+            VoiceStealing::Quietest => {
+                candidates.min_by(|a, b| voices[*a].level().total_cmp(&voices[*b].level()))
+            }
+        };
+
+        match idx {
+            Some(idx) => {
+                if stealing == VoiceStealing::FadeOldest {
+                    self.voices[idx].fade_out(Some((sequence, *trigger)));
+                } else {
+                    self.voices[idx].start(sequence, trigger);
+                }
+                self.stolen += 1;
+            }
+            None => self.dropped += 1,
+        }
+    }
+
+    /// Fades out every voice playing `step`
+    pub fn choke_step(&mut self, step: usize) {
+        for idx in self.active.iter() {
+            let voice = &mut self.voices[*idx];
+            if voice.step == step {
+                voice.choke();
+            }
+        }
+    }
+
+    /// Fades out the voices started by `sequence`
+    pub fn choke_sequence(&mut self, sequence: usize) {
+        for idx in self.active.iter() {
+            let voice = &mut self.voices[*idx];
+            if voice.sequence == sequence {
+                voice.choke();
+            }
+        }
+    }
+
+    pub fn is_playing_step(&self, step: usize) -> bool {
+        self.active.iter().any(|idx| self.voices[*idx].step == step)
+    }
+
+    /// Adds the output of every active voice to the bus of its sequence
+    pub fn render(&mut self, steps: &[Step], buses: &mut [f32]) {
+        let mut i = 0;
+        while i < self.active.len() {
+            let idx = self.active[i];
+            let voice = &mut self.voices[idx];
+            buses[voice.sequence] += voice.render(steps[voice.step].recording());
+            if !voice.is_playing {
+                // the step may have been erased or edited while the stolen voice faded out
+                if let Some((sequence, trigger)) = voice.take_pending() {
+                    if let Some(trigger) = steps[trigger.step as usize].trigger(&trigger) {
+                        voice.start(sequence, &trigger);
+                    }
+                }
+            }
+            if voice.is_playing {
+                i += 1;
+            } else {
+                self.active.swap_remove(i);
+                self.free.push(idx);
+            }
+        }
+    }
+}
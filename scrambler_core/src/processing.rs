@@ -0,0 +1,185 @@
+/// Length of the fades applied to a processed recording
+const FADE_SEC: f32 = 0.01;
+
+/// Clean up run on a finished recording, off the audio thread
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Processing {
+    pub enabled: bool,
+    /// level in dBFS below which the head of a recording counts as silence
+    pub trim_threshold: f32,
+    /// peak level in dBFS the recording is normalised to
+    pub target_peak: f32,
+}
+
+impl Default for Processing {
+    fn default() -> Self {
+        Processing {
+            enabled: false,
+            trim_threshold: -40.0,
+            target_peak: -1.0,
+        }
+    }
+}
+
+impl Processing {
+    /// Removes the DC offset and leading silence, normalises and fades the first
+    /// `length` samples of `buffer`, returns the length of the result
+    pub fn apply(&self, buffer: &mut [f32], length: usize, sample_rate: f32) -> usize {
+        let length = length.min(buffer.len());
+        if length == 0 {
+            return 0;
+        }
+
+        let offset = buffer[..length].iter().sum::<f32>() / length as f32;
+        for sample in buffer[..length].iter_mut() {
+            *sample -= offset;
+        }
+
+        // the fade in ends where the sound starts
+        let fade_len = (FADE_SEC * sample_rate) as usize;
+        let threshold = db_to_gain(self.trim_threshold);
+        let start = buffer[..length]
+            .iter()
+            .position(|sample| sample.abs() >= threshold)
+            .map_or(0, |pos| pos.saturating_sub(fade_len));
+        buffer.copy_within(start..length, 0);
+        let length = length - start;
+
+        normalise(&mut buffer[..length], self.target_peak);
+        fade_in(&mut buffer[..length], fade_len);
+        fade_out(&mut buffer[..length], fade_len);
+
+        length
+    }
+}
+
+/// Edit of a step's buffer selected in the UI
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditKind {
+    Reverse,
+    Normalise,
+    Gain,
+    FadeIn,
+    FadeOut,
+    Crop,
+    /// copies the step to the duplicate target
+    Duplicate,
+}
+
+impl EditKind {
+    pub fn get_symbol(&self) -> &str {
+        match self {
+            EditKind::Reverse => "rev",
+            EditKind::Normalise => "norm",
+            EditKind::Gain => "gain",
+            EditKind::FadeIn => "fade in",
+            EditKind::FadeOut => "fade out",
+            EditKind::Crop => "crop",
+            EditKind::Duplicate => "dup",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            EditKind::Reverse => EditKind::Normalise,
+            EditKind::Normalise => EditKind::Gain,
+            EditKind::Gain => EditKind::FadeIn,
+            EditKind::FadeIn => EditKind::FadeOut,
+            EditKind::FadeOut => EditKind::Crop,
+            EditKind::Crop => EditKind::Duplicate,
+            EditKind::Duplicate => EditKind::Reverse,
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        match self {
+            EditKind::Reverse => EditKind::Duplicate,
+            EditKind::Normalise => EditKind::Reverse,
+            EditKind::Gain => EditKind::Normalise,
+            EditKind::FadeIn => EditKind::Gain,
+            EditKind::FadeOut => EditKind::FadeIn,
+            EditKind::Crop => EditKind::FadeOut,
+            EditKind::Duplicate => EditKind::Crop,
+        }
+    }
+}
+
+/// Change made to a step's buffer by the worker
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BufferEdit {
+    Process(Processing),
+    Reverse,
+    /// to a peak level in dBFS
+    Normalise(f32),
+    /// in dB
+    Gain(f32),
+    /// length in seconds
+    FadeIn(f32),
+    FadeOut(f32),
+    /// keeps the part between two positions, 0.0 - 1.0
+    Crop(f32, f32),
+    /// leaves the buffer as it is, for copies from another step
+    Copy,
+}
+
+impl BufferEdit {
+    /// Edits the first `length` samples of `buffer`, returns the length of the result
+    pub fn apply(&self, buffer: &mut [f32], length: usize, sample_rate: f32) -> usize {
+        let length = length.min(buffer.len());
+        match *self {
+            BufferEdit::Process(processing) => {
+                return processing.apply(buffer, length, sample_rate)
+            }
+            BufferEdit::Reverse => buffer[..length].reverse(),
+            BufferEdit::Normalise(peak) => normalise(&mut buffer[..length], peak),
+            BufferEdit::Gain(db) => {
+                let gain = db_to_gain(db);
+                for sample in buffer[..length].iter_mut() {
+                    *sample *= gain;
+                }
+            }
+            BufferEdit::FadeIn(sec) => fade_in(&mut buffer[..length], (sec * sample_rate) as usize),
+            BufferEdit::FadeOut(sec) => {
+                fade_out(&mut buffer[..length], (sec * sample_rate) as usize)
+            }
+            BufferEdit::Crop(start, end) => {
+                let start = (start.clamp(0.0, 1.0) * length as f32) as usize;
+                let end = ((end.clamp(0.0, 1.0) * length as f32) as usize).max(start);
+                buffer.copy_within(start..end, 0);
+                return end - start;
+            }
+            BufferEdit::Copy => {}
+        }
+        length
+    }
+}
+
+fn normalise(buffer: &mut [f32], peak_db: f32) {
+    let peak = buffer
+        .iter()
+        .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+    if peak > 0.0 {
+        let gain = db_to_gain(peak_db) / peak;
+        for sample in buffer.iter_mut() {
+            *sample *= gain;
+        }
+    }
+}
+
+fn fade_in(buffer: &mut [f32], fade_len: usize) {
+    let fade_len = fade_len.min(buffer.len());
+    for (i, sample) in buffer.iter_mut().take(fade_len).enumerate() {
+        *sample *= i as f32 / fade_len as f32;
+    }
+}
+
+fn fade_out(buffer: &mut [f32], fade_len: usize) {
+    let fade_len = fade_len.min(buffer.len());
+    for (i, sample) in buffer.iter_mut().rev().take(fade_len).enumerate() {
+        *sample *= i as f32 / fade_len as f32;
+    }
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10.0f32.powf(db / 20.0)
+}
@@ -0,0 +1,21 @@
+pub struct Random {
+    state: u32,
+}
+
+impl Random {
+    pub fn new(seed: u32) -> Self {
+        Random { state: seed.max(1) }
+    }
+
+    pub fn next_f32(&mut self) -> f32 {
+        // xorshift32, cheap enough to run on the audio thread
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state >> 8) as f32 / (1 << 24) as f32
+    }
+
+    pub fn bipolar(&mut self) -> f32 {
+        self.next_f32() * 2.0 - 1.0
+    }
+}
@@ -0,0 +1,90 @@
+/// Comb and allpass lengths of the freeverb algorithm at 44.1 kHz
+const COMB_TUNING: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+const ALLPASS_TUNING: [usize; 4] = [556, 441, 341, 225];
+const INPUT_GAIN: f32 = 0.015;
+const WET_GAIN: f32 = 3.0;
+
+struct Comb {
+    buffer: Vec<f32>,
+    index: usize,
+    filter_store: f32,
+}
+
+impl Comb {
+    fn new(size: usize) -> Self {
+        Comb {
+            buffer: vec![0.0; size],
+            index: 0,
+            filter_store: 0.0,
+        }
+    }
+
+    fn process(&mut self, sample: f32, feedback: f32, damping: f32) -> f32 {
+        let out = self.buffer[self.index];
+        self.filter_store = out * (1.0 - damping) + self.filter_store * damping;
+        self.buffer[self.index] = sample + self.filter_store * feedback;
+        self.index = (self.index + 1) % self.buffer.len();
+        out
+    }
+}
+
+struct Allpass {
+    buffer: Vec<f32>,
+    index: usize,
+}
+
+impl Allpass {
+    fn new(size: usize) -> Self {
+        Allpass {
+            buffer: vec![0.0; size],
+            index: 0,
+        }
+    }
+
+    fn process(&mut self, sample: f32) -> f32 {
+        let buffered = self.buffer[self.index];
+        self.buffer[self.index] = sample + buffered * 0.5;
+        self.index = (self.index + 1) % self.buffer.len();
+        buffered - sample
+    }
+}
+
+/// Mono freeverb style algorithmic reverb
+pub struct Reverb {
+    combs: Vec<Comb>,
+    allpasses: Vec<Allpass>,
+    pub size: f32,
+    pub damping: f32,
+}
+
+impl Reverb {
+    pub fn new(sample_rate: f32) -> Self {
+        let scale = sample_rate / 44100.0;
+        Reverb {
+            combs: COMB_TUNING
+                .iter()
+                .map(|len| Comb::new((*len as f32 * scale) as usize))
+                .collect(),
+            allpasses: ALLPASS_TUNING
+                .iter()
+                .map(|len| Allpass::new((*len as f32 * scale) as usize))
+                .collect(),
+            size: 0.8,
+            damping: 0.5,
+        }
+    }
+
+    pub fn process(&mut self, sample: f32) -> f32 {
+        let feedback = 0.7 + self.size * 0.28;
+        let input = sample * INPUT_GAIN;
+
+        let mut out = 0.0;
+        for comb in self.combs.iter_mut() {
+            out += comb.process(input, feedback, self.damping);
+        }
+        for allpass in self.allpasses.iter_mut() {
+            out = allpass.process(out);
+        }
+        out * WET_GAIN
+    }
+}
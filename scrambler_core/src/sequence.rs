@@ -1,4 +1,7 @@
-use crate::{Counter, Subdivision};
+use crate::{
+    Counter, EnvCurve, Filter, GateLength, GrainParams, Interpolation, ModTarget, Modulation,
+    Pitch, PitchSequence, Random, Scale, Subdivision, VoiceMode, STEP_NUM,
+};
 
 #[derive(PartialEq)]
 pub enum PlayState {
@@ -40,18 +43,59 @@ impl PlayMode {
     }
 }
 
+/// Everything a step needs to start a voice
+#[derive(Clone, Copy)]
+pub struct Trigger {
+    pub step: u8,
+    pub pitch: f32,
+    /// scale the grains' pitch jitter is quantised to
+    pub scale: Scale,
+    pub gain: f32,
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+    /// time in seconds before the release starts
+    pub gate: f32,
+    pub curve: EnvCurve,
+    pub interpolation: Interpolation,
+    pub voice_mode: VoiceMode,
+    pub grain: GrainParams,
+    /// plays the step from its end to its start
+    pub reverse: bool,
+}
+
 pub struct Sequence {
     pub subdivision: Subdivision,
     pub next_subdivision: Option<Subdivision>,
     counter: Counter,
     pub current_step: u8,
-    pub pitch: f32,
+    pub pitch: Pitch,
     pub play_state: PlayState,
     pub play_mode: PlayMode,
     pub play_range: (u8, u8),
     pub gain: f32,
     pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
     pub release: f32,
+    pub gate: GateLength,
+    pub curve: EnvCurve,
+    pub interpolation: Interpolation,
+    pub voice_mode: VoiceMode,
+    pub grain: GrainParams,
+    pub filter: Filter,
+    pub delay_send: f32,
+    pub reverb_send: f32,
+    /// a new trigger chokes the voices this sequence started before
+    pub mono: bool,
+    pub modulation: Modulation,
+    pub pitch_sequence: PitchSequence,
+    /// every trigger plays backwards
+    pub reverse: bool,
+    /// chance of a trigger playing backwards, 0.0 - 1.0
+    pub reverse_probability: f32,
+    random: Random,
 }
 
 impl Sequence {
@@ -59,9 +103,10 @@ impl Sequence {
         sample_rate: f32,
         bpm: f32,
         subdivision: Subdivision,
-        pitch: f32,
+        pitch: Pitch,
         play_range: (u8, u8),
         play_mode: PlayMode,
+        seed: u32,
     ) -> Self {
         Sequence {
             subdivision,
@@ -74,7 +119,23 @@ impl Sequence {
             play_range,
             gain: 0.8,
             attack: 0.2,
+            decay: 0.1,
+            sustain: 0.8,
             release: 0.8,
+            gate: GateLength::Fraction(1.0),
+            curve: EnvCurve::Linear,
+            interpolation: Interpolation::Linear,
+            voice_mode: VoiceMode::OneShot,
+            grain: GrainParams::default(),
+            filter: Filter::new(sample_rate),
+            delay_send: 0.0,
+            reverb_send: 0.0,
+            mono: false,
+            modulation: Modulation::default(),
+            pitch_sequence: PitchSequence::default(),
+            reverse: false,
+            reverse_probability: 0.0,
+            random: Random::new(seed),
         }
     }
 
@@ -105,17 +166,14 @@ impl Sequence {
             PlayState::Playing => {
                 self.play_state = PlayState::Stopped;
                 self.counter.reset();
+                self.pitch_sequence.reset();
             }
             PlayState::Stopped => self.play_state = PlayState::Resume,
             PlayState::Resume => self.play_state = PlayState::Stopped,
         }
     }
 
-    pub fn update(
-        &mut self,
-        apply_change: bool,
-        current_bpm: f32,
-    ) -> Option<(u8, f32, f32, f32, f32)> {
+    pub fn update(&mut self, apply_change: bool, current_bpm: f32) -> Option<Trigger> {
         if apply_change {
             if self.play_state == PlayState::Resume {
                 self.play_state = PlayState::Playing;
@@ -127,45 +185,100 @@ impl Sequence {
         if self.play_state != PlayState::Playing || !self.counter.update() {
             None
         } else {
+            let (start, end) = self.live_range();
             match self.play_mode {
                 PlayMode::Forwards => {
-                    if self.current_step >= self.play_range.1 {
-                        self.current_step = self.play_range.0;
+                    if self.current_step >= end || self.current_step < start {
+                        self.current_step = start;
                     } else {
                         self.current_step += 1;
                     }
                 }
                 PlayMode::Backwards => {
-                    if self.current_step <= self.play_range.0 {
-                        self.current_step = self.play_range.1;
+                    if self.current_step <= start || self.current_step > end {
+                        self.current_step = end;
                     } else {
                         self.current_step -= 1;
                     }
                 }
                 PlayMode::BackAndForth(id) => {
                     if id == 0 {
-                        self.current_step += 1;
-                        if self.current_step >= self.play_range.1 {
+                        self.current_step = (self.current_step + 1).clamp(start, end);
+                        if self.current_step >= end {
                             self.play_mode = PlayMode::BackAndForth(1);
                         }
                     } else {
-                        self.current_step -= 1;
-                        if self.current_step <= self.play_range.0 {
+                        self.current_step = self.current_step.saturating_sub(1).clamp(start, end);
+                        if self.current_step <= start {
                             self.play_mode = PlayMode::BackAndForth(0);
                         }
                     }
                 }
             }
-            Some((
-                self.current_step,
-                self.pitch,
-                self.gain,
-                self.attack,
-                self.release,
-            ))
+            self.pitch_sequence.advance();
+            let release = self.live_value(ModTarget::Release);
+            self.filter.trigger(release);
+            let reverse = self.reverse || self.random.next_f32() < self.reverse_probability;
+            Some(Trigger {
+                step: self.current_step,
+                pitch: self.live_value(ModTarget::Pitch),
+                scale: self.pitch.scale,
+                gain: self.live_value(ModTarget::Gain),
+                attack: self.live_value(ModTarget::Attack),
+                decay: self.live_value(ModTarget::Decay),
+                sustain: self.live_value(ModTarget::Sustain),
+                release,
+                gate: self.gate.to_sec(1.0 / self.subdivision.to_hz(current_bpm)),
+                curve: self.curve,
+                interpolation: self.interpolation,
+                voice_mode: self.voice_mode,
+                grain: GrainParams {
+                    position: self.live_value(ModTarget::GrainPosition),
+                    ..self.grain
+                },
+                reverse,
+            })
+        }
+    }
+
+    /// Play range after modulation
+    pub fn live_range(&self) -> (u8, u8) {
+        let shift = |pos: u8, target: ModTarget| {
+            (pos as f32 + self.modulation.get(target))
+                .round()
+                .clamp(0.0, (STEP_NUM - 1) as f32) as u8
+        };
+        let start = shift(self.play_range.0, ModTarget::RangeStart);
+        let end = shift(self.play_range.1, ModTarget::RangeEnd).max(start);
+        (start, end)
+    }
+
+    /// Value of a parameter after modulation, pitch is returned as a ratio
+    /// and includes the pitch sequence's current offset
+    pub fn live_value(&self, target: ModTarget) -> f32 {
+        let offset = self.modulation.get(target);
+        match target {
+            ModTarget::Pitch => self
+                .pitch
+                .ratio(offset + self.pitch_sequence.current as f32),
+            ModTarget::Gain => (self.gain + offset).clamp(0.0, 1.0),
+            ModTarget::Attack => (self.attack + offset).max(0.01),
+            ModTarget::Release => (self.release + offset).max(0.01),
+            ModTarget::RangeStart => self.live_range().0 as f32,
+            ModTarget::RangeEnd => self.live_range().1 as f32,
+            ModTarget::Cutoff => (self.filter.cutoff * 2.0f32.powf(offset)).clamp(20.0, 20000.0),
+            ModTarget::Decay => (self.decay + offset).max(0.01),
+            ModTarget::Sustain => (self.sustain + offset).clamp(0.0, 1.0),
+            ModTarget::GrainPosition => (self.grain.position + offset).clamp(0.0, 1.0),
         }
     }
 
+    /// Runs the sequence's bus through its filter
+    pub fn process(&mut self, sample: f32) -> f32 {
+        self.filter.modulation = self.modulation.get(ModTarget::Cutoff);
+        self.filter.process(sample)
+    }
+
     pub fn set_bpm(&mut self, bpm: f32) {
         self.counter.set_frequency(self.subdivision.to_hz(bpm));
     }
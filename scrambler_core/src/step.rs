@@ -1,7 +1,14 @@
-use crate::{voice::Voice, AUDIO_BUFFER_SIZE_SEC, VOICE_NUM};
+use std::{mem, sync::Arc};
+
+use crate::{
+    worker::Job, BufferEdit, CaptureLength, InputHistory, Processing, Subdivision, Trigger,
+    AUDIO_BUFFER_SIZE_SEC, INIT_BPM, MAX_RECORD_SEC, SEQUENCES,
+};
 
 #[derive(PartialEq, Clone)]
 pub enum StepState {
+    /// waiting for its record arm condition
+    Armed,
     Recording,
     Recorded,
     Empty,
@@ -10,6 +17,7 @@ pub enum StepState {
 impl StepState {
     pub fn get_symbol(&self) -> &str {
         match self {
+            StepState::Armed => "  ◎  ",
             StepState::Recording => "  ●  ",
             StepState::Recorded => "  ○  ",
             StepState::Empty => "  -  ",
@@ -17,85 +25,586 @@ impl StepState {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VoiceStealing {
+    Drop,
+    Oldest,
+    Quietest,
+    FadeOldest,
+}
+
+impl VoiceStealing {
+    pub fn get_symbol(&self) -> &str {
+        match self {
+            VoiceStealing::Drop => "drop",
+            VoiceStealing::Oldest => "oldest",
+            VoiceStealing::Quietest => "quiet",
+            VoiceStealing::FadeOldest => "fade",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            VoiceStealing::Drop => VoiceStealing::Oldest,
+            VoiceStealing::Oldest => VoiceStealing::Quietest,
+            VoiceStealing::Quietest => VoiceStealing::FadeOldest,
+            VoiceStealing::FadeOldest => VoiceStealing::Drop,
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        match self {
+            VoiceStealing::Drop => VoiceStealing::FadeOldest,
+            VoiceStealing::Oldest => VoiceStealing::Drop,
+            VoiceStealing::Quietest => VoiceStealing::Oldest,
+            VoiceStealing::FadeOldest => VoiceStealing::Quietest,
+        }
+    }
+}
+
+/// Signal a step records from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordSource {
+    Input,
+    Master,
+    /// the bus of a sequence, after its filter
+    Sequence(usize),
+}
+
+impl RecordSource {
+    pub fn get_symbol(&self) -> String {
+        match self {
+            RecordSource::Input => String::from("in"),
+            RecordSource::Master => String::from("master"),
+            RecordSource::Sequence(idx) => format!("seq{}", idx + 1),
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            RecordSource::Input => RecordSource::Master,
+            RecordSource::Master => RecordSource::Sequence(0),
+            RecordSource::Sequence(idx) => {
+                if *idx + 1 >= SEQUENCES as usize {
+                    RecordSource::Input
+                } else {
+                    RecordSource::Sequence(idx + 1)
+                }
+            }
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        match self {
+            RecordSource::Input => RecordSource::Sequence(SEQUENCES as usize - 1),
+            RecordSource::Master => RecordSource::Input,
+            RecordSource::Sequence(0) => RecordSource::Master,
+            RecordSource::Sequence(idx) => RecordSource::Sequence(idx - 1),
+        }
+    }
+}
+
+/// When an armed recording starts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordArm {
+    Immediate,
+    Beat,
+    Bar,
+    /// as soon as the input gets louder than the record threshold
+    Threshold,
+    /// on the bar after a bar of clicks
+    CountIn,
+}
+
+impl RecordArm {
+    pub fn get_symbol(&self) -> &str {
+        match self {
+            RecordArm::Immediate => "now",
+            RecordArm::Beat => "beat",
+            RecordArm::Bar => "bar",
+            RecordArm::Threshold => "thr",
+            RecordArm::CountIn => "count",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            RecordArm::Immediate => RecordArm::Beat,
+            RecordArm::Beat => RecordArm::Bar,
+            RecordArm::Bar => RecordArm::Threshold,
+            RecordArm::Threshold => RecordArm::CountIn,
+            RecordArm::CountIn => RecordArm::Immediate,
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        match self {
+            RecordArm::Immediate => RecordArm::CountIn,
+            RecordArm::Beat => RecordArm::Immediate,
+            RecordArm::Bar => RecordArm::Beat,
+            RecordArm::Threshold => RecordArm::Bar,
+            RecordArm::CountIn => RecordArm::Threshold,
+        }
+    }
+}
+
+/// What a recording does to a step that already holds audio
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordMode {
+    Replace,
+    /// sums the new signal onto the recording, which is scaled by the overdub feedback
+    Overdub,
+}
+
+impl RecordMode {
+    pub fn get_symbol(&self) -> &str {
+        match self {
+            RecordMode::Replace => "repl",
+            RecordMode::Overdub => "dub",
+        }
+    }
+
+    pub fn toggle(&self) -> Self {
+        match self {
+            RecordMode::Replace => RecordMode::Overdub,
+            RecordMode::Overdub => RecordMode::Replace,
+        }
+    }
+}
+
+/// How long a recording runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordLength {
+    Seconds(f32),
+    /// beats at the tempo the recording starts at
+    Beats(u8),
+    /// until the step is recorded again, at most `MAX_RECORD_SEC`
+    Open,
+}
+
+impl RecordLength {
+    pub fn get_symbol(&self) -> String {
+        match self {
+            RecordLength::Seconds(sec) => format!("{}s", sec),
+            RecordLength::Beats(beats) => format!("{}b", beats),
+            RecordLength::Open => String::from("open"),
+        }
+    }
+
+    /// Cycles through doubling lengths in seconds, then in beats, then open
+    pub fn next(&self) -> Self {
+        match *self {
+            RecordLength::Seconds(sec) if sec < MAX_RECORD_SEC => {
+                RecordLength::Seconds((sec * 2.0).min(MAX_RECORD_SEC))
+            }
+            RecordLength::Seconds(_) => RecordLength::Beats(1),
+            RecordLength::Beats(beats) if beats < 16 => RecordLength::Beats(beats * 2),
+            RecordLength::Beats(_) => RecordLength::Open,
+            RecordLength::Open => RecordLength::Seconds(1.0),
+        }
+    }
+}
+
+/// Buffer changes that wait for the voices of a step to fade out
+#[derive(Clone, Copy, PartialEq)]
+enum PendingEdit {
+    Record,
+    Erase,
+    /// swaps in the buffer that came back from the worker
+    Swap,
+    /// swaps the buffer from before the last edit back in
+    Undo,
+}
+
 pub struct Step {
-    buffer: Vec<f32>,
+    /// allocated for the longest possible recording, shared
+    /// read-only with the worker while it is the source of an edit
+    buffer: Arc<Vec<f32>>,
+    /// second buffer of the same size, lent to the worker for edits
+    /// and holding the recording from before the last edit afterwards
+    spare: Option<Arc<Vec<f32>>>,
+    /// length of the recording in `spare` while the last edit can be undone
+    undo_length: Option<usize>,
+    /// processed buffer and its length, waiting for the voices to fade out
+    incoming: Option<(Arc<Vec<f32>>, usize)>,
+    /// counts recordings, so results of an older one are dropped
+    take: u32,
+    /// the last recording has not been processed yet
+    process_due: bool,
+    /// samples of `buffer` holding the current recording
+    pub length: usize,
     pub record_head: usize,
-    voices: Vec<Voice>,
+    pub record_length: RecordLength,
+    /// samples the running recording stops at
+    record_target: usize,
+    beat_len: usize,
+    sample_rate: f32,
     pub state: StepState,
+    /// what the current or last recording was taken from
+    pub source: RecordSource,
+    mode: RecordMode,
+    /// the running recording is layered onto the existing one
+    overdub: bool,
+    /// steps sharing a choke group cut each other off
+    pub choke_group: Option<u8>,
+    /// triggers of this step always play backwards
+    pub reverse: bool,
+    pending_edit: Option<PendingEdit>,
+    arm: RecordArm,
+    /// bar lines left before a count-in recording starts
+    count_in: u8,
+    /// state to return to when an armed recording is cancelled
+    disarmed_state: StepState,
+    /// a recording replaced the buffer, the voices still playing it fade out
+    choke_due: bool,
 }
 
 impl Step {
     pub fn new(sample_rate: f32) -> Self {
-        let buffer_size = sample_rate * AUDIO_BUFFER_SIZE_SEC;
+        let buffer_size = sample_rate * MAX_RECORD_SEC;
         Step {
-            buffer: vec![0.0; buffer_size as usize],
+            buffer: Arc::new(vec![0.0; buffer_size as usize]),
+            spare: Some(Arc::new(vec![0.0; buffer_size as usize])),
+            undo_length: None,
+            incoming: None,
+            take: 0,
+            process_due: false,
+            length: 0,
             record_head: 0,
-            voices: {
-                let mut voices: Vec<Voice> = Vec::with_capacity(VOICE_NUM as usize);
-                for _ in 0..VOICE_NUM {
-                    voices.push(Voice::new(sample_rate, buffer_size as usize));
-                }
-                voices
-            },
+            record_length: RecordLength::Seconds(AUDIO_BUFFER_SIZE_SEC),
+            record_target: 0,
+            beat_len: (sample_rate / Subdivision::Quarter.to_hz(INIT_BPM)) as usize,
+            sample_rate,
             state: StepState::Empty,
+            source: RecordSource::Input,
+            mode: RecordMode::Replace,
+            overdub: false,
+            choke_group: None,
+            reverse: false,
+            pending_edit: None,
+            arm: RecordArm::Immediate,
+            count_in: 0,
+            disarmed_state: StepState::Empty,
+            choke_due: false,
         }
     }
 
-    pub fn set_attack(&mut self, val: f32) {
-        for voice in self.voices.iter_mut() {
-            voice.set_attack(val);
+    /// Writes the next sample of the recording, when overdubbing the
+    /// existing audio is scaled by `feedback` and the sample added to it
+    pub fn record(&mut self, sample: f32, feedback: f32) {
+        // recordings only start once no edit reads the buffer anymore
+        if let Some(buffer) = Arc::get_mut(&mut self.buffer) {
+            if self.overdub && self.record_head < self.length {
+                buffer[self.record_head] = buffer[self.record_head] * feedback + sample;
+            } else {
+                buffer[self.record_head] = sample;
+            }
+        }
+        self.record_head += 1;
+        if self.record_head >= self.record_target {
+            self.finish_recording();
         }
     }
 
-    pub fn set_release(&mut self, val: f32) {
-        for voice in self.voices.iter_mut() {
-            voice.set_release(val);
+    fn finish_recording(&mut self) {
+        self.length = if self.overdub {
+            self.length.max(self.record_head)
+        } else {
+            self.record_head
+        };
+        // an overdub is layered onto audio that was processed already
+        self.process_due = !self.overdub;
+        self.overdub = false;
+        self.state = StepState::Recorded;
+        self.record_head = 0;
+    }
+
+    pub fn set_bpm(&mut self, bpm: f32) {
+        self.beat_len = (self.sample_rate / Subdivision::Quarter.to_hz(bpm)) as usize;
+    }
+
+    fn target_len(&self) -> usize {
+        let len = match self.record_length {
+            RecordLength::Seconds(sec) => (sec * self.sample_rate) as usize,
+            RecordLength::Beats(beats) => beats as usize * self.beat_len,
+            RecordLength::Open => self.buffer.len(),
+        };
+        len.clamp(1, self.buffer.len())
+    }
+
+    /// Copies the recent input into the spare buffer, it replaces the
+    /// recording once the voices faded out, nothing happens while the
+    /// spare is lent out or another edit waits
+    pub fn capture(&mut self, history: &InputHistory, length: CaptureLength) {
+        if self.pending_edit.is_some() || self.spare.is_none() {
+            return;
+        }
+        self.new_take();
+        if let Some(mut buffer) = self.spare.take() {
+            let length = match Arc::get_mut(&mut buffer) {
+                Some(samples) => history.copy_to(samples, length, self.beat_len),
+                None => 0,
+            };
+            self.incoming = Some((buffer, length));
+            self.process_due = true;
+            self.source = RecordSource::Input;
+            self.request_edit(PendingEdit::Swap);
         }
     }
 
-    pub fn record(&mut self, sample: f32) {
-        self.buffer[self.record_head] = sample;
-        self.record_head += 1;
-        if self.record_head >= self.buffer.len() {
-            self.state = StepState::Recorded;
-            self.record_head = 0;
+    /// Arms a recording, arming an armed step again cancels it
+    /// and recording an open ended step again finishes it
+    pub fn arm(&mut self, source: RecordSource, arm: RecordArm, mode: RecordMode) {
+        if self.state == StepState::Recording && self.record_length == RecordLength::Open {
+            self.finish_recording();
+            return;
+        }
+        if self.state == StepState::Armed {
+            self.state = self.disarmed_state.clone();
+            return;
         }
+        self.mode = mode;
+        if arm == RecordArm::Immediate {
+            self.start_recording(source);
+            return;
+        }
+        self.source = source;
+        self.arm = arm;
+        self.count_in = 2;
+        self.disarmed_state = self.state.clone();
+        self.state = StepState::Armed;
+    }
+
+    /// Starts an armed recording once its condition is met
+    pub fn check_arm(&mut self, beat: bool, downbeat: bool, input_level: f32, threshold: f32) {
+        if self.state != StepState::Armed || self.pending_edit == Some(PendingEdit::Record) {
+            return;
+        }
+        let start = match self.arm {
+            RecordArm::Immediate => true,
+            RecordArm::Beat => beat,
+            RecordArm::Bar => downbeat,
+            RecordArm::Threshold => input_level >= threshold,
+            RecordArm::CountIn => {
+                if downbeat {
+                    self.count_in = self.count_in.saturating_sub(1);
+                }
+                self.count_in == 0
+            }
+        };
+        if start {
+            self.start_recording(self.source);
+        }
+    }
+
+    /// True during the bar of clicks before a count-in recording
+    pub fn is_counting_in(&self) -> bool {
+        self.state == StepState::Armed && self.arm == RecordArm::CountIn && self.count_in == 1
+    }
+
+    /// Starts recording on this sample, so armed recordings land on the beat.
+    /// An overdub keeps the length of the recording and plays on while it
+    /// runs, the voices only have to fade out when the step gets replaced
+    pub fn start_recording(&mut self, source: RecordSource) {
+        self.source = source;
+        self.new_take();
+        self.overdub = self.mode == RecordMode::Overdub && self.length > 0;
+        self.record_target = if self.overdub {
+            self.length
+        } else {
+            self.target_len()
+        };
+        if self.is_shared() {
+            // the worker has to finish reading the buffer first
+            self.request_edit(PendingEdit::Record);
+            return;
+        }
+        // the recording replaces whatever edit was waiting
+        self.pending_edit = None;
+        self.choke_due = !self.overdub;
+        self.apply_edit(PendingEdit::Record);
+    }
+
+    /// True once after a recording started replacing the buffer
+    pub fn take_choke(&mut self) -> bool {
+        mem::take(&mut self.choke_due)
     }
 
     pub fn erase(&mut self) {
-        self.buffer.fill_with(Default::default);
-        self.state = StepState::Empty;
-        self.record_head = 0;
+        self.new_take();
+        self.request_edit(PendingEdit::Erase);
     }
 
-    pub fn play(&mut self, pitch: f32, gain: f32, attack: f32, release: f32) {
-        self.set_attack(attack);
-        self.set_release(release);
+    /// Invalidates the recording that may be with the worker
+    fn new_take(&mut self) {
+        self.take = self.take.wrapping_add(1);
+        self.process_due = false;
+        self.undo_length = None;
+        if let Some((buffer, _)) = self.incoming.take() {
+            self.spare = Some(buffer);
+        }
+    }
 
-        for voice in self.voices.iter_mut() {
-            if !voice.is_playing {
-                voice.pitch = pitch;
-                voice.gain = gain;
-                voice.is_playing = true;
-                break;
-            }
+    /// The job processing a recording that just finished, recordings
+    /// finished while processing is off are left as they are
+    pub fn process_recording(&mut self, step: usize, processing: Processing) -> Option<Job> {
+        if !processing.enabled {
+            self.process_due = false;
+        }
+        if !self.process_due || !self.accepts_edit() {
+            return None;
+        }
+        let (source, length) = self.share()?;
+        self.process_due = false;
+        self.job(step, source, length, BufferEdit::Process(processing))
+    }
+
+    /// The spare is free to take the result of an edit
+    pub fn accepts_edit(&self) -> bool {
+        matches!(self.state, StepState::Recorded | StepState::Empty)
+            && self.pending_edit.is_none()
+            && self.spare.is_some()
+    }
+
+    /// The recording and its length, for the worker to read as the
+    /// source of an edit while the step keeps playing it
+    pub fn share(&self) -> Option<(Arc<Vec<f32>>, usize)> {
+        if self.state == StepState::Recorded && self.pending_edit.is_none() {
+            Some((Arc::clone(&self.buffer), self.length))
+        } else {
+            None
+        }
+    }
+
+    /// The worker still reads the buffer, as the source of an edit
+    fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.buffer) > 1
+    }
+
+    /// Lends the spare to the worker, to be filled from `source` and edited
+    pub fn job(
+        &mut self,
+        step: usize,
+        source: Arc<Vec<f32>>,
+        length: usize,
+        edit: BufferEdit,
+    ) -> Option<Job> {
+        if !self.accepts_edit() {
+            return None;
         }
+        self.spare.take().map(|buffer| Job {
+            step,
+            take: self.take,
+            source,
+            buffer,
+            length,
+            edit,
+        })
     }
 
-    pub fn render(&mut self) -> f32 {
-        let mut sample = 0.0;
-        for voice in self.voices.iter_mut() {
-            if voice.is_playing {
-                let (pos, env, gain) = voice.render();
-                let pos_int = pos as usize;
-                let next_pos = (pos_int + 1) % self.buffer.len();
-                let frac = pos - pos_int as f32;
+    /// The worker overwrites the spare, so the recording an undo would restore is gone
+    pub fn forget_undo(&mut self) {
+        self.undo_length = None;
+    }
+
+    /// The samples of the current recording
+    pub fn recording(&self) -> &[f32] {
+        &self.buffer[..self.length]
+    }
 
-                let next_sample =
-                    self.buffer[pos_int] * (1.0 - frac) + self.buffer[next_pos] * frac;
-                sample += next_sample * env * gain;
+    /// Swaps the recording from before the last edit back in,
+    /// undoing again redoes the edit
+    pub fn undo(&mut self) {
+        if self.undo_length.is_some() && self.spare.is_some() && self.pending_edit.is_none() {
+            self.request_edit(PendingEdit::Undo);
+        }
+    }
+
+    /// Swaps in an edited recording, unless the step was recorded again since
+    pub fn receive(&mut self, job: Job) {
+        if job.take == self.take && matches!(self.state, StepState::Recorded | StepState::Empty) {
+            self.incoming = Some((job.buffer, job.length));
+            self.request_edit(PendingEdit::Swap);
+        } else {
+            self.reclaim(job.buffer);
+        }
+    }
+
+    /// Takes back a spare that was lent out
+    pub fn reclaim(&mut self, buffer: Arc<Vec<f32>>) {
+        self.spare = Some(buffer);
+    }
+
+    /// Changing the buffer under a playing voice clicks, so the voices
+    /// are faded out first, see `apply_pending_edit`
+    fn request_edit(&mut self, edit: PendingEdit) {
+        self.pending_edit = Some(edit);
+    }
+
+    pub fn has_pending_edit(&self) -> bool {
+        self.pending_edit.is_some()
+    }
+
+    /// Applies the waiting edit, once no voice plays this step anymore
+    /// and the worker finished reading the buffer
+    pub fn apply_pending_edit(&mut self) {
+        if self.is_shared() {
+            return;
+        }
+        if let Some(edit) = self.pending_edit.take() {
+            self.apply_edit(edit);
+        }
+    }
+
+    fn apply_edit(&mut self, edit: PendingEdit) {
+        match edit {
+            PendingEdit::Record => {
+                self.state = StepState::Recording;
+                self.record_head = 0;
+            }
+            PendingEdit::Erase => {
+                if let Some(buffer) = Arc::get_mut(&mut self.buffer) {
+                    buffer.fill_with(Default::default);
+                }
+                self.length = 0;
+                self.state = StepState::Empty;
+                self.record_head = 0;
             }
+            PendingEdit::Swap => {
+                if let Some((mut buffer, length)) = self.incoming.take() {
+                    mem::swap(&mut self.buffer, &mut buffer);
+                    self.spare = Some(buffer);
+                    self.undo_length = Some(self.length);
+                    self.set_length(length);
+                }
+            }
+            PendingEdit::Undo => {
+                if let (Some(length), Some(spare)) = (self.undo_length, self.spare.as_mut()) {
+                    mem::swap(&mut self.buffer, spare);
+                    self.undo_length = Some(self.length);
+                    self.set_length(length);
+                }
+            }
+        }
+    }
+
+    fn set_length(&mut self, length: usize) {
+        self.length = length;
+        self.state = if length > 0 {
+            StepState::Recorded
+        } else {
+            StepState::Empty
+        };
+    }
+
+    /// The trigger a voice plays this step with, none while the step is silent
+    pub fn trigger(&self, trigger: &Trigger) -> Option<Trigger> {
+        if self.pending_edit.is_some() || self.length == 0 {
+            return None;
         }
-        sample
+        Some(Trigger {
+            reverse: trigger.reverse || self.reverse,
+            ..*trigger
+        })
     }
 }
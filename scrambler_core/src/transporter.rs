@@ -4,6 +4,10 @@ pub struct Transporter {
     pub bar: u8,
     pub quater: u8,
     pub sixteenth: u8,
+    /// set for the sample a new beat starts on
+    pub beat: bool,
+    /// set for the sample a new bar starts on
+    pub downbeat: bool,
     counter: Counter,
     quantisation: Subdivision,
 }
@@ -16,6 +20,8 @@ impl Transporter {
             quater: 0,
             bar: 0,
             sixteenth: 0,
+            beat: false,
+            downbeat: false,
             counter: Counter::new(sample_rate, freq),
             quantisation: Subdivision::Quarter,
         }
@@ -23,6 +29,8 @@ impl Transporter {
 
     pub fn update(&mut self) -> bool {
         let mut ret: bool = false;
+        self.beat = false;
+        self.downbeat = false;
         if self.counter.update() {
             self.sixteenth += 1;
             if self.quantisation == Subdivision::Sixteenth {
@@ -30,6 +38,7 @@ impl Transporter {
             }
             if self.sixteenth >= 4 {
                 self.sixteenth = 0;
+                self.beat = true;
                 self.quater += 1;
                 if self.quantisation == Subdivision::Quarter {
                     ret = true;
@@ -37,6 +46,7 @@ impl Transporter {
             }
             if self.quater >= 4 {
                 self.quater = 0;
+                self.downbeat = true;
                 self.bar += 1;
             }
             if self.bar >= 4 {
@@ -46,6 +56,11 @@ impl Transporter {
         ret
     }
 
+    /// Position within the current bar, 0.0 - 1.0
+    pub fn phase(&self) -> f32 {
+        ((self.quater * 4 + self.sixteenth) as f32 + self.counter.phase()) / 16.0
+    }
+
     pub fn set_bpm(&mut self, bpm: f32) {
         self.counter
             .set_frequency(Subdivision::Sixteenth.to_hz(bpm))
@@ -1,67 +1,158 @@
-#[derive(PartialEq)]
-enum EnvState {
-    Attack,
-    Release,
-}
+use crate::{Envelope, GrainCloud, Interpolation, Trigger, VoiceMode, DECLICK_SEC};
 
 pub struct Voice {
     play_head: f32,
     pub is_playing: bool,
     pub gain: f32,
-    env: f32,
-    env_inc_attack: f32,
-    env_inc_release: f32,
-    env_state: EnvState,
-    buffer_size: usize,
+    env: Envelope,
     pub pitch: f32,
     sample_rate: f32,
+    mode: VoiceMode,
+    interpolation: Interpolation,
+    reverse: bool,
+    cloud: GrainCloud,
+    /// index of the sequence whose bus this voice plays into
+    pub sequence: usize,
+    /// index of the step whose buffer this voice plays
+    pub step: usize,
+    /// samples rendered since the voice was started
+    pub age: usize,
+    fade: f32,
+    fade_dec: f32,
+    /// trigger to start once a fade out has finished
+    pending: Option<(usize, Trigger)>,
 }
 
 impl Voice {
-    pub fn new(sample_rate: f32, buffer_size: usize) -> Self {
+    pub fn new(sample_rate: f32, seed: u32) -> Self {
         Voice {
             play_head: 0.0,
             is_playing: false,
             gain: 0.0,
-            env: 0.0,
-            env_inc_attack: 1.0 / sample_rate / 0.01,
-            env_inc_release: 1.0 / sample_rate / 1.0,
-            env_state: EnvState::Attack,
-            buffer_size,
+            env: Envelope::new(sample_rate),
             pitch: 1.0,
             sample_rate,
+            mode: VoiceMode::OneShot,
+            interpolation: Interpolation::Linear,
+            reverse: false,
+            cloud: GrainCloud::new(sample_rate, seed),
+            sequence: 0,
+            step: 0,
+            age: 0,
+            fade: 1.0,
+            fade_dec: 0.0,
+            pending: None,
         }
     }
 
-    pub fn render(&mut self) -> (f32, f32, f32) {
-        match self.env_state {
-            EnvState::Attack => self.env += self.env_inc_attack,
-            EnvState::Release => self.env -= self.env_inc_release,
-        };
+    pub fn start(&mut self, sequence: usize, trigger: &Trigger) {
+        self.stop();
+        self.sequence = sequence;
+        self.step = trigger.step as usize;
+        self.pitch = trigger.pitch;
+        self.gain = trigger.gain;
+        // envelope segments follow the playback speed, the gate doesn't
+        self.env.trigger(
+            trigger.attack / self.pitch,
+            trigger.decay / self.pitch,
+            trigger.sustain,
+            trigger.release / self.pitch,
+            trigger.gate,
+            trigger.curve,
+        );
+        self.mode = trigger.voice_mode;
+        self.interpolation = trigger.interpolation;
+        self.reverse = trigger.reverse;
+        if self.mode == VoiceMode::Granular {
+            self.cloud.start(
+                trigger.grain,
+                trigger.pitch,
+                trigger.scale,
+                trigger.interpolation,
+                trigger.reverse,
+            );
+        }
+        self.is_playing = true;
+    }
+
+    fn stop(&mut self) {
+        self.is_playing = false;
+        self.play_head = 0.0;
+        self.env.reset();
+        self.age = 0;
+        self.fade = 1.0;
+        self.fade_dec = 0.0;
+    }
+
+    /// Fades the voice out quickly, `pending` can be started once it stopped
+    pub fn fade_out(&mut self, pending: Option<(usize, Trigger)>) {
+        self.fade_dec = 1.0 / (DECLICK_SEC * self.sample_rate);
+        self.pending = pending;
+    }
 
-        if self.env >= 1.0 {
-            self.env_state = EnvState::Release;
+    /// Fades the voice out and drops anything waiting to start on it
+    pub fn choke(&mut self) {
+        self.pending = None;
+        if !self.is_fading() {
+            self.fade_out(None);
         }
+    }
+
+    /// The trigger waiting for the fade out to finish
+    pub fn take_pending(&mut self) -> Option<(usize, Trigger)> {
+        self.pending.take()
+    }
 
-        if self.env <= 0.0 && self.env_state == EnvState::Release {
-            self.env = 0.0;
+    pub fn is_fading(&self) -> bool {
+        self.fade_dec > 0.0
+    }
+
+    /// Current output level, used to find the quietest voice
+    pub fn level(&self) -> f32 {
+        self.env.value * self.gain * self.fade
+    }
+
+    /// Renders the next sample, `buffer` only holds the recorded part of the step
+    pub fn render(&mut self, buffer: &[f32]) -> f32 {
+        let env = self.env.update();
+        let (sample, mut gain) = (self.render_source(buffer), self.gain * self.fade);
+        self.age += 1;
+
+        let mut finished = self.env.is_finished();
+        if self.mode == VoiceMode::OneShot {
+            // fade out before the end of the buffer instead of stopping hard
+            let remaining = (buffer.len() as f32 - self.play_head) / self.pitch;
+            gain *= (remaining / (DECLICK_SEC * self.sample_rate)).min(1.0);
+
+            self.play_head += self.pitch;
+            finished |= self.play_head >= buffer.len() as f32;
         }
 
-        self.play_head += self.pitch;
-        if self.play_head >= self.buffer_size as f32 {
-            self.is_playing = false;
-            self.play_head = 0.0;
-            self.env_state = EnvState::Attack;
+        if self.is_fading() {
+            self.fade -= self.fade_dec;
+            finished |= self.fade <= 0.0;
         }
 
-        (self.play_head, self.env, self.gain)
-    }
+        if finished {
+            self.stop();
+        }
 
-    pub fn set_attack(&mut self, val: f32) {
-        self.env_inc_attack = 1.0 / self.sample_rate / (val / self.pitch);
+        sample * env * gain
     }
 
-    pub fn set_release(&mut self, val: f32) {
-        self.env_inc_release = 1.0 / self.sample_rate / (val / self.pitch);
+    /// `play_head` counts the samples played, a reversed
+    /// voice reads that far from the end of the buffer
+    fn render_source(&mut self, buffer: &[f32]) -> f32 {
+        match self.mode {
+            VoiceMode::OneShot => {
+                let pos = if self.reverse {
+                    buffer.len() as f32 - 1.0 - self.play_head
+                } else {
+                    self.play_head
+                };
+                self.interpolation.read(buffer, pos, self.pitch)
+            }
+            VoiceMode::Granular => self.cloud.render(buffer),
+        }
     }
 }
@@ -0,0 +1,64 @@
+use std::{
+    sync::{
+        mpsc::{sync_channel, Receiver, SyncSender, TrySendError},
+        Arc,
+    },
+    thread,
+};
+
+use crate::{BufferEdit, STEP_NUM};
+
+/// A step's buffer on its way to the worker and back
+pub struct Job {
+    pub step: usize,
+    /// recording of the step the result is for
+    pub take: u32,
+    /// recording the edit starts from, shared with the step playing it
+    /// and copied into `buffer` by the worker
+    pub source: Arc<Vec<f32>>,
+    pub buffer: Arc<Vec<f32>>,
+    pub length: usize,
+    pub edit: BufferEdit,
+}
+
+/// Background thread for the work that is too slow for the audio thread,
+/// buffers are moved back and forth so neither side allocates
+pub struct Worker {
+    jobs: SyncSender<Job>,
+    results: Receiver<Job>,
+}
+
+impl Worker {
+    pub fn new(sample_rate: f32) -> Self {
+        let (jobs, job_receiver) = sync_channel::<Job>(STEP_NUM as usize);
+        let (result_sender, results) = sync_channel(STEP_NUM as usize);
+
+        thread::spawn(move || {
+            while let Ok(mut job) = job_receiver.recv() {
+                // the spare is only ever owned by one side
+                if let Some(buffer) = Arc::get_mut(&mut job.buffer) {
+                    let length = job.length.min(job.source.len()).min(buffer.len());
+                    buffer[..length].copy_from_slice(&job.source[..length]);
+                    job.length = job.edit.apply(buffer, length, sample_rate);
+                }
+                if result_sender.send(job).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Worker { jobs, results }
+    }
+
+    /// Hands the job back if the worker is busy
+    pub fn submit(&self, job: Job) -> Result<(), Job> {
+        match self.jobs.try_send(job) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(job) | TrySendError::Disconnected(job)) => Err(job),
+        }
+    }
+
+    pub fn try_recv(&self) -> Option<Job> {
+        self.results.try_recv().ok()
+    }
+}
@@ -21,12 +21,74 @@ enum SetEvent {
     SetSubdivision((usize, Subdivision)),
     Toggle(usize),
     SetPitch((usize, i8)),
+    SetFineTune((usize, i8)),
+    SetScale((usize, Scale)),
+    SetPitchSequenceLength((usize, u8)),
+    SetPitchSequenceStep((usize, usize, i8)),
     SetRangeStart((usize, u8)),
     SetRangeEnd((usize, u8)),
     SetDir((usize, PlayMode)),
     SetAttack((usize, f32)),
     SetRelease((usize, f32)),
     SetGain((usize, f32)),
+    SetVoiceMode((usize, VoiceMode)),
+    SetGrainSize((usize, f32)),
+    SetGrainDensity((usize, f32)),
+    SetGrainPosition((usize, f32)),
+    SetPositionJitter((usize, f32)),
+    SetPitchJitter((usize, f32)),
+    SetGrainWindow((usize, GrainWindow)),
+    SetFilterMode((usize, FilterMode)),
+    SetCutoff((usize, f32)),
+    SetResonance((usize, f32)),
+    SetFilterEnv((usize, f32)),
+    SetDelaySend((usize, f32)),
+    SetReverbSend((usize, f32)),
+    SetDelayTime(Subdivision),
+    SetDelayFeedback(f32),
+    SetReverbSize(f32),
+    SetReverbDamping(f32),
+    SetMasterGain(f32),
+    ToggleLimiter,
+    SetVoiceStealing(VoiceStealing),
+    SetPolyphony(usize),
+    SetMono((usize, bool)),
+    SetReverse((usize, bool)),
+    SetReverseProbability((usize, f32)),
+    SetStepReverse((usize, bool)),
+    SetChokeGroup((usize, Option<u8>)),
+    SetDecay((usize, f32)),
+    SetSustain((usize, f32)),
+    SetGate((usize, GateLength)),
+    SetCurve((usize, EnvCurve)),
+    SetInterpolation((usize, Interpolation)),
+    SetLfoShape((usize, LfoShape)),
+    SetLfoRate((usize, LfoRate)),
+    SetLfoDepth((usize, f32)),
+    SetLfoTarget((usize, Option<(usize, ModTarget)>)),
+    SetModSource((usize, ModSource)),
+    SetModDestination((usize, Option<(usize, ModTarget)>)),
+    SetModAmount((usize, f32)),
+    SetInputGain(f32),
+    SetMonitorLevel(f32),
+    SetRecordSource(RecordSource),
+    Capture(usize),
+    SetCaptureLength(CaptureLength),
+    SetRecordArm(RecordArm),
+    SetRecordThreshold(f32),
+    SetRecordMode(RecordMode),
+    SetOverdubFeedback(f32),
+    ToggleProcessing,
+    SetTrimThreshold(f32),
+    SetTargetPeak(f32),
+    Edit(usize),
+    Undo(usize),
+    SetEditKind(EditKind),
+    SetEditGain(f32),
+    SetEditFade(f32),
+    SetCrop((f32, f32)),
+    SetDuplicateTarget(usize),
+    SetRecordLength((usize, RecordLength)),
 }
 
 fn main() -> io::Result<()> {
@@ -67,9 +129,6 @@ fn main() -> io::Result<()> {
             //let output_r = state.output_r.as_mut_slice(ps);
             let input = state.input.as_slice(ps);
 
-            output_l.copy_from_slice(input);
-            //output_r.copy_from_slice(input);
-
             let events = state.receiver.try_iter();
             for event in events {
                 match event {
@@ -81,6 +140,16 @@ fn main() -> io::Result<()> {
                     }
                     SetEvent::Toggle(index) => state.sequencer.toggle(index),
                     SetEvent::SetPitch((index, pitch)) => state.sequencer.set_pitch(index, pitch),
+                    SetEvent::SetFineTune((index, cents)) => {
+                        state.sequencer.set_fine_tune(index, cents)
+                    }
+                    SetEvent::SetScale((index, scale)) => state.sequencer.set_scale(index, scale),
+                    SetEvent::SetPitchSequenceLength((index, length)) => {
+                        state.sequencer.set_pitch_sequence_length(index, length)
+                    }
+                    SetEvent::SetPitchSequenceStep((index, step, semitone)) => state
+                        .sequencer
+                        .set_pitch_sequence_step(index, step, semitone),
                     SetEvent::SetRangeStart((index, pitch)) => {
                         state.sequencer.set_range_start(index, pitch)
                     }
@@ -93,12 +162,115 @@ fn main() -> io::Result<()> {
                     SetEvent::SetAttack((index, val)) => state.sequencer.set_attack(val, index),
                     SetEvent::SetRelease((index, val)) => state.sequencer.set_release(val, index),
                     SetEvent::SetGain((index, val)) => state.sequencer.set_gain(val, index),
+                    SetEvent::SetVoiceMode((index, mode)) => {
+                        state.sequencer.set_voice_mode(index, mode)
+                    }
+                    SetEvent::SetGrainSize((index, val)) => {
+                        state.sequencer.set_grain_size(index, val)
+                    }
+                    SetEvent::SetGrainDensity((index, val)) => {
+                        state.sequencer.set_grain_density(index, val)
+                    }
+                    SetEvent::SetGrainPosition((index, val)) => {
+                        state.sequencer.set_grain_position(index, val)
+                    }
+                    SetEvent::SetPositionJitter((index, val)) => {
+                        state.sequencer.set_position_jitter(index, val)
+                    }
+                    SetEvent::SetPitchJitter((index, val)) => {
+                        state.sequencer.set_pitch_jitter(index, val)
+                    }
+                    SetEvent::SetGrainWindow((index, window)) => {
+                        state.sequencer.set_grain_window(index, window)
+                    }
+                    SetEvent::SetFilterMode((index, mode)) => {
+                        state.sequencer.set_filter_mode(index, mode)
+                    }
+                    SetEvent::SetCutoff((index, val)) => state.sequencer.set_cutoff(index, val),
+                    SetEvent::SetResonance((index, val)) => {
+                        state.sequencer.set_resonance(index, val)
+                    }
+                    SetEvent::SetFilterEnv((index, val)) => {
+                        state.sequencer.set_filter_env(index, val)
+                    }
+                    SetEvent::SetDelaySend((index, val)) => {
+                        state.sequencer.set_delay_send(index, val)
+                    }
+                    SetEvent::SetReverbSend((index, val)) => {
+                        state.sequencer.set_reverb_send(index, val)
+                    }
+                    SetEvent::SetDelayTime(val) => state.sequencer.set_delay_subdivision(val),
+                    SetEvent::SetDelayFeedback(val) => state.sequencer.set_delay_feedback(val),
+                    SetEvent::SetReverbSize(val) => state.sequencer.set_reverb_size(val),
+                    SetEvent::SetReverbDamping(val) => state.sequencer.set_reverb_damping(val),
+                    SetEvent::SetMasterGain(val) => state.sequencer.set_master_gain(val),
+                    SetEvent::ToggleLimiter => state.sequencer.toggle_limiter(),
+                    SetEvent::SetVoiceStealing(val) => state.sequencer.set_voice_stealing(val),
+                    SetEvent::SetPolyphony(val) => state.sequencer.set_polyphony(val),
+                    SetEvent::SetMono((index, val)) => state.sequencer.set_mono(index, val),
+                    SetEvent::SetReverse((index, val)) => state.sequencer.set_reverse(index, val),
+                    SetEvent::SetReverseProbability((index, val)) => {
+                        state.sequencer.set_reverse_probability(index, val)
+                    }
+                    SetEvent::SetStepReverse((index, val)) => {
+                        state.sequencer.set_step_reverse(index, val)
+                    }
+                    SetEvent::SetChokeGroup((index, group)) => {
+                        state.sequencer.set_choke_group(index, group)
+                    }
+                    SetEvent::SetDecay((index, val)) => state.sequencer.set_decay(val, index),
+                    SetEvent::SetSustain((index, val)) => state.sequencer.set_sustain(val, index),
+                    SetEvent::SetGate((index, val)) => state.sequencer.set_gate(val, index),
+                    SetEvent::SetCurve((index, val)) => state.sequencer.set_curve(val, index),
+                    SetEvent::SetInterpolation((index, val)) => {
+                        state.sequencer.set_interpolation(val, index)
+                    }
+                    SetEvent::SetLfoShape((index, val)) => {
+                        state.sequencer.set_lfo_shape(index, val)
+                    }
+                    SetEvent::SetLfoRate((index, val)) => state.sequencer.set_lfo_rate(index, val),
+                    SetEvent::SetLfoDepth((index, val)) => {
+                        state.sequencer.set_lfo_depth(index, val)
+                    }
+                    SetEvent::SetLfoTarget((index, val)) => {
+                        state.sequencer.set_lfo_target(index, val)
+                    }
+                    SetEvent::SetModSource((slot, val)) => {
+                        state.sequencer.set_mod_source(slot, val)
+                    }
+                    SetEvent::SetModDestination((slot, val)) => {
+                        state.sequencer.set_mod_destination(slot, val)
+                    }
+                    SetEvent::SetModAmount((slot, val)) => {
+                        state.sequencer.set_mod_amount(slot, val)
+                    }
+                    SetEvent::SetInputGain(val) => state.sequencer.set_input_gain(val),
+                    SetEvent::SetMonitorLevel(val) => state.sequencer.set_monitor_level(val),
+                    SetEvent::SetRecordSource(val) => state.sequencer.set_record_source(val),
+                    SetEvent::Capture(idx) => state.sequencer.capture(idx),
+                    SetEvent::SetCaptureLength(val) => state.sequencer.set_capture_length(val),
+                    SetEvent::SetRecordArm(val) => state.sequencer.set_record_arm(val),
+                    SetEvent::SetRecordThreshold(val) => state.sequencer.set_record_threshold(val),
+                    SetEvent::SetRecordMode(val) => state.sequencer.set_record_mode(val),
+                    SetEvent::SetOverdubFeedback(val) => state.sequencer.set_overdub_feedback(val),
+                    SetEvent::ToggleProcessing => state.sequencer.toggle_processing(),
+                    SetEvent::SetTrimThreshold(val) => state.sequencer.set_trim_threshold(val),
+                    SetEvent::SetTargetPeak(val) => state.sequencer.set_target_peak(val),
+                    SetEvent::Edit(idx) => state.sequencer.edit(idx),
+                    SetEvent::Undo(idx) => state.sequencer.undo(idx),
+                    SetEvent::SetEditKind(val) => state.sequencer.set_edit_kind(val),
+                    SetEvent::SetEditGain(val) => state.sequencer.set_edit_gain(val),
+                    SetEvent::SetEditFade(val) => state.sequencer.set_edit_fade(val),
+                    SetEvent::SetCrop(val) => state.sequencer.set_crop(val),
+                    SetEvent::SetDuplicateTarget(val) => state.sequencer.set_duplicate_target(val),
+                    SetEvent::SetRecordLength((step, val)) => {
+                        state.sequencer.set_record_length(step, val)
+                    }
                 }
             }
 
-            for sample in output_l.iter_mut() {
-                state.sequencer.render(sample);
-            }
+            state.sequencer.render_block(input, output_l);
+            //output_r.copy_from_slice(output_l);
 
             jack::Control::Continue
         },
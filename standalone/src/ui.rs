@@ -1,4 +1,4 @@
-use std::{io, time::Duration};
+use std::{fs, io, time::Duration};
 
 use crossbeam::channel::Sender;
 use ratatui::{
@@ -9,7 +9,11 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph},
     Frame,
 };
-use scrambler_core::{DrawData, Output, STEP_NUM};
+use scrambler_core::{
+    DrawData, GateLength, LfoRate, ModSlot, ModTarget, Output, Scale, Subdivision, CHOKE_GROUPS,
+    LFO_NUM, MAX_CENTS, MAX_POLYPHONY, MAX_SEMITONES, MOD_SLOTS, PITCH_STEPS, SCALE_NOTES,
+    SEQUENCES, STEP_NUM,
+};
 use symbols::{
     BLANK, FULL, RANGE_END, RANGE_SINGLE, RANGE_START, SELECTED, STEP_ACTIVE, STEP_INACTIVE,
 };
@@ -18,6 +22,9 @@ use crate::SetEvent;
 
 mod symbols;
 
+/// Where the modulation matrix is saved to and loaded from
+const MATRIX_FILE: &str = "matrix.txt";
+
 pub struct Ui {
     pub state: State,
 }
@@ -33,18 +40,21 @@ impl Ui {
                 selected: Selected::Div,
                 selected_area: SelectedArea::Sequence(0),
                 selected_global: SelectedGlobal::Bpm,
-                semitones: vec![0; 3],
+                selected_lfo: SelectedLfo::Shape,
+                selected_mod: SelectedMod::Source,
+                matrix_status: None,
             },
         }
     }
 
     pub fn draw(&mut self, frame: &mut Frame) {
         let draw_data = self.state.draw_data.read();
+        let height = 1 + 9 + 4 + 13 * SEQUENCES as u16 + LFO_NUM as u16 + MOD_SLOTS as u16 + 1;
         let layout_vertical = Layout::default()
             .direction(Direction::Vertical)
             .constraints(vec![
                 Constraint::Min(0),
-                Constraint::Min(0),
+                Constraint::Length(height),
                 Constraint::Min(0),
             ])
             .split(frame.area());
@@ -61,17 +71,19 @@ impl Ui {
         let main_area = Layout::default()
             .direction(Direction::Vertical)
             .constraints(vec![
-                Constraint::Length(1), // transporter
-                Constraint::Length(2), // status
-                Constraint::Length(2), // steps
-                Constraint::Min(0),    // steps
+                Constraint::Length(1),                     // transporter
+                Constraint::Length(9),                     // status
+                Constraint::Length(4),                     // steps
+                Constraint::Length(13 * SEQUENCES as u16), // sequences
+                Constraint::Length(LFO_NUM as u16),        // lfos
+                Constraint::Min(0),                        // mod matrix
             ])
             .split(layout_horizontal[1]);
 
         let positions = &draw_data.positions;
         let sequences = Layout::default()
             .direction(Direction::Vertical)
-            .constraints(vec![Constraint::Length(5); positions.len()])
+            .constraints(vec![Constraint::Length(13); positions.len()])
             .split(main_area[3]);
 
         let mode = match self.state.mode {
@@ -79,26 +91,131 @@ impl Ui {
             Mode::Erase => "erase",
             Mode::RangeStart => "range start",
             Mode::RangeEnd => "range end",
+            Mode::Choke => "choke group",
+            Mode::Capture => "capture",
+            Mode::Length => "record length",
+            Mode::Reverse => "reverse",
+            Mode::Edit => "edit",
+            Mode::Undo => "undo last edit",
         };
 
-        let mut status_spans = vec![Span::from(format!(" BPM: {} ", draw_data.bpm))];
+        let mut fx_spans = vec![
+            Span::from(format!(" BPM: {} ", draw_data.bpm)),
+            Span::from(format!(
+                " Dly: {} ",
+                draw_data.delay_subdivision.get_symbol()
+            )),
+            Span::from(format!(" Fb: {:.1} ", draw_data.delay_feedback)),
+            Span::from(format!(" Rev: {:.1} ", draw_data.reverb_size)),
+            Span::from(format!(" Damp: {:.1} ", draw_data.reverb_damping)),
+        ];
 
-        if self.state.selected_area == SelectedArea::Global {
-            match self.state.selected_global {
-                SelectedGlobal::Bpm => {
-                    status_spans[0] = status_spans[0]
-                        .clone()
-                        .style(Style::default().fg(Color::Red))
+        let mut master_spans = vec![
+            Span::from(format!(" Vol: {:.1} ", draw_data.master_gain)),
+            Span::from(format!(
+                " Lim: {} ",
+                if draw_data.limiter_enabled {
+                    "on"
+                } else {
+                    "off"
                 }
-            }
+            )),
+            Span::from(format!(" GR: {:.1}dB ", draw_data.gain_reduction)),
+            Span::from(format!(
+                " Steal: {} ",
+                draw_data.voice_stealing.get_symbol()
+            )),
+            Span::from(format!(" Poly: {} ", draw_data.polyphony)),
+        ];
+
+        // meter over the lower 60dB
+        let meter_db = 20.0 * draw_data.input_level.max(1e-6).log10();
+        let meter_len = (((meter_db + 60.0) / 60.0).clamp(0.0, 1.0) * 10.0) as usize;
+        let mut input_spans = vec![
+            Span::from(format!(" In: {:.1} ", draw_data.input_gain)),
+            Span::from(format!(" Mon: {:.1} ", draw_data.monitor_level)),
+            Span::from(format!(
+                " [{}{}] ",
+                "|".repeat(meter_len),
+                " ".repeat(10 - meter_len)
+            )),
+        ];
+        if draw_data.input_clipped {
+            input_spans.push(Span::styled(
+                " CLIP ",
+                Style::default().fg(Color::Red).bold(),
+            ));
+        }
+
+        let mut record_spans = vec![
+            Span::from(format!(" Src: {} ", draw_data.record_source.get_symbol())),
+            Span::from(format!(" Cap: {} ", draw_data.capture_length.get_symbol())),
+            Span::from(format!(" Arm: {} ", draw_data.record_arm.get_symbol())),
+            Span::from(format!(" Thr: {:.2} ", draw_data.record_threshold)),
+        ];
+
+        let mut process_spans = vec![
+            Span::from(format!(" Rec: {} ", draw_data.record_mode.get_symbol())),
+            Span::from(format!(" Fb: {:.2} ", draw_data.overdub_feedback)),
+            Span::from(format!(
+                " Proc: {} ",
+                if draw_data.processing.enabled {
+                    "on"
+                } else {
+                    "off"
+                }
+            )),
+            Span::from(format!(" Trim: {}dB ", draw_data.processing.trim_threshold)),
+            Span::from(format!(" Peak: {:.1}dB ", draw_data.processing.target_peak)),
+        ];
+
+        let mut edit_spans = vec![
+            Span::from(format!(" Edit: {} ", draw_data.edit_kind.get_symbol())),
+            Span::from(format!(" Gain: {:+}dB ", draw_data.edit_gain)),
+            Span::from(format!(" Fade: {:.2}s ", draw_data.edit_fade)),
+        ];
+
+        let mut crop_spans = vec![
+            Span::from(format!(" Crop: {:.2}", draw_data.crop.0)),
+            Span::from(format!("-{:.2} ", draw_data.crop.1)),
+            Span::from(format!(" Dup: {} ", draw_data.duplicate_target + 1)),
+        ];
+
+        let voice_spans = vec![
+            Span::from(format!(" Voices: {} ", draw_data.active_voices)),
+            Span::from(format!(" Dropped: {} ", draw_data.dropped_voices)),
+            Span::from(format!(" Stolen: {} ", draw_data.stolen_voices)),
+        ];
+
+        if self.state.selected_area == SelectedArea::Global {
+            let (line, column) = self.state.selected_global.position();
+            let spans = match line {
+                0 => &mut fx_spans,
+                1 => &mut master_spans,
+                2 => &mut input_spans,
+                3 => &mut record_spans,
+                4 => &mut process_spans,
+                5 => &mut edit_spans,
+                _ => &mut crop_spans,
+            };
+            spans[column] = spans[column].clone().style(Style::default().fg(Color::Red));
         }
 
-        let status_bar = Paragraph::new(Text::from(Line::from(status_spans)))
-            .block(Block::new().borders(Borders::BOTTOM));
+        let status_bar = Paragraph::new(Text::from(vec![
+            Line::from(fx_spans),
+            Line::from(master_spans),
+            Line::from(input_spans),
+            Line::from(record_spans),
+            Line::from(process_spans),
+            Line::from(edit_spans),
+            Line::from(crop_spans),
+            Line::from(voice_spans),
+        ]))
+        .block(Block::new().borders(Borders::BOTTOM));
 
         frame.render_widget(status_bar, main_area[1]);
 
-        let transporter_span = vec![
+        let mut transporter_span = vec![
             Span::from(format!(
                 " {}:{}   |",
                 draw_data.transporter.1 + 1,
@@ -106,6 +223,9 @@ impl Ui {
             )),
             Span::from(format!("   Mode: {}", mode)),
         ];
+        if let Some(status) = self.state.matrix_status {
+            transporter_span.push(Span::from(format!("   |   {}", status)));
+        }
         frame.render_widget(
             Paragraph::new(Text::from(Line::from(transporter_span))),
             main_area[0],
@@ -117,15 +237,41 @@ impl Ui {
             .map(|state| Span::from(state.get_symbol()))
             .collect();
 
+        let choke_group_span: Vec<Span> = draw_data
+            .choke_groups
+            .iter()
+            .map(|group| match group {
+                Some(group) => Span::from(format!("  {}  ", (b'A' + group) as char)),
+                None => Span::from(BLANK),
+            })
+            .collect();
+
+        let record_length_span: Vec<Span> = draw_data
+            .record_lengths
+            .iter()
+            .map(|length| Span::from(format!("{:^5}", length.get_symbol())).dim())
+            .collect();
+
+        let step_reverse_span: Vec<Span> = draw_data
+            .step_reverses
+            .iter()
+            .map(|reverse| Span::from(if *reverse { "  ◀  " } else { BLANK }))
+            .collect();
+
         frame.render_widget(
-            Paragraph::new(Text::from(Line::from(step_status_span))),
+            Paragraph::new(Text::from(vec![
+                Line::from(step_status_span),
+                Line::from(choke_group_span),
+                Line::from(record_length_span),
+                Line::from(step_reverse_span),
+            ])),
             main_area[2],
         );
 
         for (i, position) in positions.iter().enumerate() {
             let sequence_area = Layout::default()
                 .direction(Direction::Vertical)
-                .constraints(vec![Constraint::Length(1); 3])
+                .constraints(vec![Constraint::Length(1); 12])
                 .split(sequences[i]);
 
             // render steps
@@ -143,19 +289,21 @@ impl Ui {
                 sequence_area[0],
             );
 
-            // render range
+            // render range, including the modulation
+            let modulated = &draw_data.modulated[i];
+            let (start, end) = (
+                modulated[ModTarget::RangeStart.index()] as usize,
+                modulated[ModTarget::RangeEnd.index()] as usize,
+            );
             let mut range = vec![Span::styled(BLANK, Style::default().bold()); STEP_NUM as usize];
-            for y in draw_data.ranges[i].0..draw_data.ranges[i].1 {
-                range[y as usize] = Span::styled(FULL, Style::default().bold());
+            for span in range.iter_mut().take(end).skip(start) {
+                *span = Span::styled(FULL, Style::default().bold());
             }
-            if draw_data.ranges[i].0 == draw_data.ranges[i].1 {
-                range[draw_data.ranges[i].0 as usize] =
-                    Span::styled(RANGE_SINGLE, Style::default().bold());
+            if start == end {
+                range[start] = Span::styled(RANGE_SINGLE, Style::default().bold());
             } else {
-                range[draw_data.ranges[i].0 as usize] =
-                    Span::styled(RANGE_START, Style::default().bold());
-                range[draw_data.ranges[i].1 as usize] =
-                    Span::styled(RANGE_END, Style::default().bold());
+                range[start] = Span::styled(RANGE_START, Style::default().bold());
+                range[end] = Span::styled(RANGE_END, Style::default().bold());
             }
 
             frame.render_widget(
@@ -167,51 +315,323 @@ impl Ui {
             let mut param_lines = vec![
                 Span::from(format!(" Div: {} ", draw_data.subdivisions[i].get_symbol())),
                 Span::from(format!(" Dir: {} ", draw_data.dirs[i].get_symbol())),
-                Span::from(format!(" Pitch: {} ", self.state.semitones[i])),
-                Span::from(format!(" Gain: {:.1} ", draw_data.gains[i])),
-                Span::from(format!(" Att: {:.1} ", draw_data.attacks[i])),
-                Span::from(format!(" Rel: {:.1} ", draw_data.releases[i])),
+                Span::from(format!(
+                    " Pitch: {} ",
+                    with_live(
+                        draw_data.pitches[i].note(),
+                        12.0 * modulated[ModTarget::Pitch.index()].log2(),
+                        0
+                    )
+                )),
+                Span::from(format!(
+                    " Gain: {} ",
+                    with_live(draw_data.gains[i], modulated[ModTarget::Gain.index()], 1)
+                )),
+            ];
+
+            let grain = &draw_data.grains[i];
+            let mut grain_lines = vec![
+                Span::from(format!(" Mode: {} ", draw_data.voice_modes[i].get_symbol())),
+                Span::from(format!(" Size: {:.2} ", grain.size)),
+                Span::from(format!(" Dens: {:.0} ", grain.density)),
+                Span::from(format!(" Pos: {:.2} ", grain.position)),
+            ];
+
+            let mut jitter_lines = vec![
+                Span::from(format!(" PJit: {:.2} ", grain.position_jitter)),
+                Span::from(format!(" TJit: {:.1} ", grain.pitch_jitter)),
+                Span::from(format!(" Win: {} ", grain.window.get_symbol())),
+            ];
+
+            let mut filter_lines = vec![
+                Span::from(format!(
+                    " Filt: {} ",
+                    draw_data.filter_modes[i].get_symbol()
+                )),
+                Span::from(format!(
+                    " Cut: {} ",
+                    with_live(
+                        draw_data.cutoffs[i],
+                        modulated[ModTarget::Cutoff.index()],
+                        0
+                    )
+                )),
+                Span::from(format!(" Res: {:.1} ", draw_data.resonances[i])),
+                Span::from(format!(" Env: {:.1} ", draw_data.filter_envs[i])),
+            ];
+
+            let mut voice_lines = vec![
+                Span::from(format!(
+                    " Mono: {} ",
+                    if draw_data.monos[i] { "on" } else { "off" }
+                )),
+                Span::from(match draw_data.gates[i] {
+                    GateLength::Fraction(fraction) => format!(" Gate: {:.2}x ", fraction),
+                    GateLength::Ms(ms) => format!(" Gate: {:.0}ms ", ms),
+                }),
+                Span::from(format!(" Dly: {:.1} ", draw_data.delay_sends[i])),
+                Span::from(format!(" Rev: {:.1} ", draw_data.reverb_sends[i])),
+            ];
+
+            let mut envelope_lines = vec![
+                Span::from(format!(
+                    " Att: {} ",
+                    with_live(
+                        draw_data.attacks[i],
+                        modulated[ModTarget::Attack.index()],
+                        1
+                    )
+                )),
+                Span::from(format!(" Dec: {:.1} ", draw_data.decays[i])),
+                Span::from(format!(" Sus: {:.1} ", draw_data.sustains[i])),
+                Span::from(format!(
+                    " Rel: {} ",
+                    with_live(
+                        draw_data.releases[i],
+                        modulated[ModTarget::Release.index()],
+                        1
+                    )
+                )),
+            ];
+
+            let mut shape_lines = vec![
+                Span::from(format!(" Curve: {} ", draw_data.curves[i].get_symbol())),
+                Span::from(format!(
+                    " Int: {} ",
+                    draw_data.interpolations[i].get_symbol()
+                )),
+            ];
+
+            let pitch = &draw_data.pitches[i];
+            let mut tune_lines = vec![
+                Span::from(format!(" Fine: {:+}c ", pitch.cents)),
+                Span::from(format!(" Scale: {} ", pitch.scale.get_symbol())),
+                Span::from(format!(
+                    " Rvs: {} ",
+                    if draw_data.reverses[i] { "on" } else { "off" }
+                )),
+                Span::from(format!(" RvsP: {:.2} ", draw_data.reverse_probabilities[i])),
             ];
 
+            // notes of a preset scale are dimmed, toggling one makes the scale custom
+            let mut note_lines = vec![Span::from(" Notes: ")];
+            for note in 0..SCALE_NOTES {
+                let span = Span::from(if pitch.scale.mask() & (1 << note) != 0 {
+                    " ■ "
+                } else {
+                    " □ "
+                });
+                note_lines.push(match pitch.scale {
+                    Scale::Custom(_) => span,
+                    _ => span.dim(),
+                });
+            }
+
+            // the offset playing right now is bold, offsets past the length are dimmed
+            let pitch_sequence = &draw_data.pitch_sequences[i];
+            let playing =
+                (pitch_sequence.position + pitch_sequence.length - 1) % pitch_sequence.length;
+            let mut pitch_sequence_lines =
+                vec![Span::from(format!(" Seq: {} ", pitch_sequence.length))];
+            for (step, offset) in pitch_sequence.offsets.iter().enumerate() {
+                let span = Span::from(format!(" {:+} ", offset));
+                pitch_sequence_lines.push(if step as u8 >= pitch_sequence.length {
+                    span.dim()
+                } else if step as u8 == playing {
+                    span.bold()
+                } else {
+                    span
+                });
+            }
+
             if self.state.selected_area == SelectedArea::Sequence(i) {
-                match self.state.selected {
-                    Selected::Div => {
-                        param_lines[0] = param_lines[0]
-                            .clone()
-                            .style(Style::default().fg(Color::Red))
-                    }
-                    Selected::Dir => {
-                        param_lines[1] = param_lines[1]
-                            .clone()
-                            .style(Style::default().fg(Color::Red))
-                    }
-                    Selected::Pitch => {
-                        param_lines[2] = param_lines[2]
-                            .clone()
-                            .style(Style::default().fg(Color::Red))
-                    }
-                    Selected::Gain => {
-                        param_lines[3] = param_lines[3]
-                            .clone()
-                            .style(Style::default().fg(Color::Red))
-                    }
-                    Selected::Attack => {
-                        param_lines[4] = param_lines[4]
-                            .clone()
-                            .style(Style::default().fg(Color::Red))
-                    }
-                    Selected::Release => {
-                        param_lines[5] = param_lines[5]
-                            .clone()
-                            .style(Style::default().fg(Color::Red))
-                    }
+                let (line, column) = self.state.selected.position();
+                let spans = match line {
+                    0 => &mut param_lines,
+                    1 => &mut grain_lines,
+                    2 => &mut jitter_lines,
+                    3 => &mut filter_lines,
+                    4 => &mut voice_lines,
+                    5 => &mut envelope_lines,
+                    6 => &mut shape_lines,
+                    7 => &mut tune_lines,
+                    8 => &mut note_lines,
+                    _ => &mut pitch_sequence_lines,
                 };
+                spans[column] = spans[column].clone().style(Style::default().fg(Color::Red));
             };
 
             let params = Paragraph::new(Text::from(Line::from(param_lines)));
+            let grain_params = Paragraph::new(Text::from(Line::from(grain_lines)));
+            let jitter_params = Paragraph::new(Text::from(Line::from(jitter_lines)));
+            let filter_params = Paragraph::new(Text::from(Line::from(filter_lines)));
+            let voice_params = Paragraph::new(Text::from(Line::from(voice_lines)));
+            let envelope_params = Paragraph::new(Text::from(Line::from(envelope_lines)));
+            let shape_params = Paragraph::new(Text::from(Line::from(shape_lines)));
+            let tune_params = Paragraph::new(Text::from(Line::from(tune_lines)));
+            let note_params = Paragraph::new(Text::from(Line::from(note_lines)));
+            let pitch_sequence_params =
+                Paragraph::new(Text::from(Line::from(pitch_sequence_lines)));
 
+            frame.render_widget(grain_params, sequence_area[3]);
+            frame.render_widget(jitter_params, sequence_area[4]);
+            frame.render_widget(filter_params, sequence_area[5]);
+            frame.render_widget(voice_params, sequence_area[6]);
+            frame.render_widget(envelope_params, sequence_area[7]);
+            frame.render_widget(shape_params, sequence_area[8]);
+            frame.render_widget(tune_params, sequence_area[9]);
+            frame.render_widget(note_params, sequence_area[10]);
+            frame.render_widget(pitch_sequence_params, sequence_area[11]);
             frame.render_widget(params, sequence_area[2]);
         }
+
+        let lfo_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(1); LFO_NUM as usize])
+            .split(main_area[4]);
+
+        for i in 0..LFO_NUM as usize {
+            let mut lfo_line = vec![
+                Span::from(format!(" LFO{} ", i + 1)).bold(),
+                Span::from(format!(" {} ", draw_data.lfo_shapes[i].get_symbol())),
+                Span::from(match draw_data.lfo_rates[i] {
+                    LfoRate::Sync(_) => " sync ",
+                    LfoRate::Hz(_) => " free ",
+                }),
+                Span::from(match draw_data.lfo_rates[i] {
+                    LfoRate::Sync(subdivision) => format!(" {} ", subdivision.get_symbol()),
+                    LfoRate::Hz(hz) => format!(" {:.2}Hz ", hz),
+                }),
+                Span::from(format!(" Depth: {:.1} ", draw_data.lfo_depths[i])),
+                Span::from(match draw_data.lfo_targets[i] {
+                    Some((sequence, target)) => {
+                        format!(" -> {} {} ", sequence + 1, target.get_symbol())
+                    }
+                    None => String::from(" -> off "),
+                }),
+                Span::from(format!(" {:+.2} ", draw_data.lfo_values[i])),
+            ];
+
+            if self.state.selected_area == SelectedArea::Lfo(i) {
+                let column = self.state.selected_lfo.position();
+                lfo_line[column] = lfo_line[column]
+                    .clone()
+                    .style(Style::default().fg(Color::Red));
+            }
+
+            frame.render_widget(
+                Paragraph::new(Text::from(Line::from(lfo_line))),
+                lfo_area[i],
+            );
+        }
+
+        let matrix_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(1); MOD_SLOTS as usize])
+            .split(main_area[5]);
+
+        for i in 0..MOD_SLOTS as usize {
+            let mut mod_line = vec![
+                Span::from(format!(" MOD{} ", i + 1)).bold(),
+                Span::from(format!(" {} ", draw_data.mod_sources[i].get_symbol())),
+                Span::from(match draw_data.mod_destinations[i] {
+                    Some((sequence, target)) => {
+                        format!(" -> {} {} ", sequence + 1, target.get_symbol())
+                    }
+                    None => String::from(" -> off "),
+                }),
+                Span::from(format!(" Amt: {:+.1} ", draw_data.mod_amounts[i])),
+                Span::from(format!(" {:+.2} ", draw_data.mod_values[i])),
+            ];
+
+            if self.state.selected_area == SelectedArea::Matrix(i) {
+                let column = self.state.selected_mod.position();
+                mod_line[column] = mod_line[column]
+                    .clone()
+                    .style(Style::default().fg(Color::Red));
+            }
+
+            frame.render_widget(
+                Paragraph::new(Text::from(Line::from(mod_line))),
+                matrix_area[i],
+            );
+        }
+    }
+}
+
+/// Formats a parameter, followed by its modulated value if that differs
+fn with_live(base: f32, live: f32, precision: usize) -> String {
+    if (base - live).abs() < 0.005 {
+        format!("{:.*}", precision, base)
+    } else {
+        format!("{:.*}~{:.*}", precision, base, precision, live)
+    }
+}
+
+/// Writes the matrix slots to `MATRIX_FILE`, one per line
+fn save_matrix(draw_data: &DrawData) -> &'static str {
+    let text: String = (0..MOD_SLOTS as usize)
+        .map(|i| {
+            let slot = ModSlot {
+                source: draw_data.mod_sources[i],
+                destination: draw_data.mod_destinations[i],
+                amount: draw_data.mod_amounts[i],
+            };
+            slot.to_line() + "\n"
+        })
+        .collect();
+    match fs::write(MATRIX_FILE, text) {
+        Ok(()) => "matrix saved",
+        Err(_) => "matrix save failed",
+    }
+}
+
+/// Sends the slots saved in `MATRIX_FILE`, the matrix stays
+/// as it is when the file can't be read
+fn load_matrix(sender: &Sender<SetEvent>) -> &'static str {
+    let slots: Option<Vec<ModSlot>> = fs::read_to_string(MATRIX_FILE).ok().and_then(|text| {
+        text.lines()
+            .take(MOD_SLOTS as usize)
+            .map(ModSlot::from_line)
+            .collect()
+    });
+    match slots {
+        Some(slots) => {
+            for (i, slot) in slots.into_iter().enumerate() {
+                sender
+                    .send(SetEvent::SetModSource((i, slot.source)))
+                    .unwrap();
+                sender
+                    .send(SetEvent::SetModDestination((i, slot.destination)))
+                    .unwrap();
+                sender
+                    .send(SetEvent::SetModAmount((i, slot.amount)))
+                    .unwrap();
+            }
+            "matrix loaded"
+        }
+        None => "matrix load failed",
+    }
+}
+
+/// Cycles through off and every parameter of every sequence
+fn step_mod_target(target: Option<(usize, ModTarget)>, up: bool) -> Option<(usize, ModTarget)> {
+    let slots = SEQUENCES as usize * ModTarget::ALL.len();
+    let current = match target {
+        Some((sequence, target)) => sequence * ModTarget::ALL.len() + target.index() + 1,
+        None => 0,
+    };
+    let next = if up {
+        (current + 1) % (slots + 1)
+    } else {
+        (current + slots) % (slots + 1)
+    };
+    match next {
+        0 => None,
+        slot => Some((
+            (slot - 1) / ModTarget::ALL.len(),
+            ModTarget::ALL[(slot - 1) % ModTarget::ALL.len()],
+        )),
     }
 }
 
@@ -220,6 +640,14 @@ enum Mode {
     Erase,
     RangeStart,
     RangeEnd,
+    Choke,
+    /// copies the recent input into a step
+    Capture,
+    Length,
+    Reverse,
+    /// applies the selected buffer edit to a step
+    Edit,
+    Undo,
 }
 
 #[derive(PartialEq)]
@@ -228,25 +656,160 @@ enum Selected {
     Dir,
     Pitch,
     Gain,
+    VoiceMode,
+    GrainSize,
+    GrainDensity,
+    GrainPosition,
+    PositionJitter,
+    PitchJitter,
+    GrainWindow,
+    FilterMode,
+    Cutoff,
+    Resonance,
+    FilterEnv,
+    Mono,
+    Gate,
+    DelaySend,
+    ReverbSend,
     Attack,
+    Decay,
+    Sustain,
     Release,
+    Curve,
+    Interpolation,
+    Fine,
+    Scale,
+    Reverse,
+    ReverseProbability,
+    /// note of the scale this many semitones up
+    ScaleNote(u8),
+    PitchSequenceLength,
+    /// offset of the pitch sequence at this index
+    PitchSequenceStep(u8),
 }
 
 #[derive(PartialEq)]
 enum SelectedGlobal {
     Bpm,
+    DelayTime,
+    DelayFeedback,
+    ReverbSize,
+    ReverbDamping,
+    MasterGain,
+    Limiter,
+    VoiceStealing,
+    Polyphony,
+    InputGain,
+    Monitor,
+    RecordSource,
+    CaptureLength,
+    RecordArm,
+    RecordThreshold,
+    RecordMode,
+    OverdubFeedback,
+    Processing,
+    TrimThreshold,
+    TargetPeak,
+    EditKind,
+    EditGain,
+    EditFade,
+    CropStart,
+    CropEnd,
+    DuplicateTarget,
 }
 
 impl SelectedGlobal {
     fn next(&mut self) {
         *self = match self {
-            SelectedGlobal::Bpm => SelectedGlobal::Bpm,
+            SelectedGlobal::Bpm => SelectedGlobal::DelayTime,
+            SelectedGlobal::DelayTime => SelectedGlobal::DelayFeedback,
+            SelectedGlobal::DelayFeedback => SelectedGlobal::ReverbSize,
+            SelectedGlobal::ReverbSize => SelectedGlobal::ReverbDamping,
+            SelectedGlobal::ReverbDamping => SelectedGlobal::MasterGain,
+            SelectedGlobal::MasterGain => SelectedGlobal::Limiter,
+            SelectedGlobal::Limiter => SelectedGlobal::VoiceStealing,
+            SelectedGlobal::VoiceStealing => SelectedGlobal::Polyphony,
+            SelectedGlobal::Polyphony => SelectedGlobal::InputGain,
+            SelectedGlobal::InputGain => SelectedGlobal::Monitor,
+            SelectedGlobal::Monitor => SelectedGlobal::RecordSource,
+            SelectedGlobal::RecordSource => SelectedGlobal::CaptureLength,
+            SelectedGlobal::CaptureLength => SelectedGlobal::RecordArm,
+            SelectedGlobal::RecordArm => SelectedGlobal::RecordThreshold,
+            SelectedGlobal::RecordThreshold => SelectedGlobal::RecordMode,
+            SelectedGlobal::RecordMode => SelectedGlobal::OverdubFeedback,
+            SelectedGlobal::OverdubFeedback => SelectedGlobal::Processing,
+            SelectedGlobal::Processing => SelectedGlobal::TrimThreshold,
+            SelectedGlobal::TrimThreshold => SelectedGlobal::TargetPeak,
+            SelectedGlobal::TargetPeak => SelectedGlobal::EditKind,
+            SelectedGlobal::EditKind => SelectedGlobal::EditGain,
+            SelectedGlobal::EditGain => SelectedGlobal::EditFade,
+            SelectedGlobal::EditFade => SelectedGlobal::CropStart,
+            SelectedGlobal::CropStart => SelectedGlobal::CropEnd,
+            SelectedGlobal::CropEnd => SelectedGlobal::DuplicateTarget,
+            SelectedGlobal::DuplicateTarget => SelectedGlobal::Bpm,
         }
     }
 
     fn prev(&mut self) {
         *self = match self {
-            SelectedGlobal::Bpm => SelectedGlobal::Bpm,
+            SelectedGlobal::Bpm => SelectedGlobal::DuplicateTarget,
+            SelectedGlobal::DuplicateTarget => SelectedGlobal::CropEnd,
+            SelectedGlobal::CropEnd => SelectedGlobal::CropStart,
+            SelectedGlobal::CropStart => SelectedGlobal::EditFade,
+            SelectedGlobal::EditFade => SelectedGlobal::EditGain,
+            SelectedGlobal::EditGain => SelectedGlobal::EditKind,
+            SelectedGlobal::EditKind => SelectedGlobal::TargetPeak,
+            SelectedGlobal::TargetPeak => SelectedGlobal::TrimThreshold,
+            SelectedGlobal::TrimThreshold => SelectedGlobal::Processing,
+            SelectedGlobal::Processing => SelectedGlobal::OverdubFeedback,
+            SelectedGlobal::OverdubFeedback => SelectedGlobal::RecordMode,
+            SelectedGlobal::RecordMode => SelectedGlobal::RecordThreshold,
+            SelectedGlobal::RecordThreshold => SelectedGlobal::RecordArm,
+            SelectedGlobal::RecordArm => SelectedGlobal::CaptureLength,
+            SelectedGlobal::CaptureLength => SelectedGlobal::RecordSource,
+            SelectedGlobal::RecordSource => SelectedGlobal::Monitor,
+            SelectedGlobal::Monitor => SelectedGlobal::InputGain,
+            SelectedGlobal::InputGain => SelectedGlobal::Polyphony,
+            SelectedGlobal::Polyphony => SelectedGlobal::VoiceStealing,
+            SelectedGlobal::VoiceStealing => SelectedGlobal::Limiter,
+            SelectedGlobal::Limiter => SelectedGlobal::MasterGain,
+            SelectedGlobal::MasterGain => SelectedGlobal::ReverbDamping,
+            SelectedGlobal::ReverbDamping => SelectedGlobal::ReverbSize,
+            SelectedGlobal::ReverbSize => SelectedGlobal::DelayFeedback,
+            SelectedGlobal::DelayFeedback => SelectedGlobal::DelayTime,
+            SelectedGlobal::DelayTime => SelectedGlobal::Bpm,
+        }
+    }
+
+    /// Line and column of the parameter in the status bar
+    fn position(&self) -> (usize, usize) {
+        match self {
+            SelectedGlobal::Bpm => (0, 0),
+            SelectedGlobal::DelayTime => (0, 1),
+            SelectedGlobal::DelayFeedback => (0, 2),
+            SelectedGlobal::ReverbSize => (0, 3),
+            SelectedGlobal::ReverbDamping => (0, 4),
+            SelectedGlobal::MasterGain => (1, 0),
+            SelectedGlobal::Limiter => (1, 1),
+            SelectedGlobal::VoiceStealing => (1, 3),
+            SelectedGlobal::Polyphony => (1, 4),
+            SelectedGlobal::InputGain => (2, 0),
+            SelectedGlobal::Monitor => (2, 1),
+            SelectedGlobal::RecordSource => (3, 0),
+            SelectedGlobal::CaptureLength => (3, 1),
+            SelectedGlobal::RecordArm => (3, 2),
+            SelectedGlobal::RecordThreshold => (3, 3),
+            SelectedGlobal::RecordMode => (4, 0),
+            SelectedGlobal::OverdubFeedback => (4, 1),
+            SelectedGlobal::Processing => (4, 2),
+            SelectedGlobal::TrimThreshold => (4, 3),
+            SelectedGlobal::TargetPeak => (4, 4),
+            SelectedGlobal::EditKind => (5, 0),
+            SelectedGlobal::EditGain => (5, 1),
+            SelectedGlobal::EditFade => (5, 2),
+            SelectedGlobal::CropStart => (6, 0),
+            SelectedGlobal::CropEnd => (6, 1),
+            SelectedGlobal::DuplicateTarget => (6, 2),
         }
     }
 }
@@ -254,6 +817,8 @@ impl SelectedGlobal {
 #[derive(PartialEq)]
 enum SelectedArea {
     Sequence(usize),
+    Lfo(usize),
+    Matrix(usize),
     Global,
 }
 
@@ -262,11 +827,25 @@ impl SelectedArea {
         *self = match self {
             SelectedArea::Sequence(idx) => {
                 if *idx >= 2 {
-                    SelectedArea::Global
+                    SelectedArea::Lfo(0)
                 } else {
                     SelectedArea::Sequence(*idx + 1)
                 }
             }
+            SelectedArea::Lfo(idx) => {
+                if *idx + 1 >= LFO_NUM as usize {
+                    SelectedArea::Matrix(0)
+                } else {
+                    SelectedArea::Lfo(*idx + 1)
+                }
+            }
+            SelectedArea::Matrix(idx) => {
+                if *idx + 1 >= MOD_SLOTS as usize {
+                    SelectedArea::Global
+                } else {
+                    SelectedArea::Matrix(*idx + 1)
+                }
+            }
             SelectedArea::Global => SelectedArea::Sequence(0),
         }
     }
@@ -274,13 +853,103 @@ impl SelectedArea {
     fn prev(&mut self) {
         *self = match self {
             SelectedArea::Sequence(idx) => {
-                if *idx <= 0 {
+                if *idx == 0 {
                     SelectedArea::Global
                 } else {
                     SelectedArea::Sequence(*idx - 1)
                 }
             }
-            SelectedArea::Global => SelectedArea::Sequence(2),
+            SelectedArea::Lfo(idx) => {
+                if *idx == 0 {
+                    SelectedArea::Sequence(2)
+                } else {
+                    SelectedArea::Lfo(*idx - 1)
+                }
+            }
+            SelectedArea::Matrix(idx) => {
+                if *idx == 0 {
+                    SelectedArea::Lfo(LFO_NUM as usize - 1)
+                } else {
+                    SelectedArea::Matrix(*idx - 1)
+                }
+            }
+            SelectedArea::Global => SelectedArea::Matrix(MOD_SLOTS as usize - 1),
+        }
+    }
+}
+
+#[derive(PartialEq)]
+enum SelectedLfo {
+    Shape,
+    RateMode,
+    Rate,
+    Depth,
+    Target,
+}
+
+impl SelectedLfo {
+    fn next(&mut self) {
+        *self = match self {
+            SelectedLfo::Shape => SelectedLfo::RateMode,
+            SelectedLfo::RateMode => SelectedLfo::Rate,
+            SelectedLfo::Rate => SelectedLfo::Depth,
+            SelectedLfo::Depth => SelectedLfo::Target,
+            SelectedLfo::Target => SelectedLfo::Shape,
+        }
+    }
+
+    fn prev(&mut self) {
+        *self = match self {
+            SelectedLfo::Shape => SelectedLfo::Target,
+            SelectedLfo::Target => SelectedLfo::Depth,
+            SelectedLfo::Depth => SelectedLfo::Rate,
+            SelectedLfo::Rate => SelectedLfo::RateMode,
+            SelectedLfo::RateMode => SelectedLfo::Shape,
+        }
+    }
+
+    /// Column of the parameter in an lfo line, after the label
+    fn position(&self) -> usize {
+        match self {
+            SelectedLfo::Shape => 1,
+            SelectedLfo::RateMode => 2,
+            SelectedLfo::Rate => 3,
+            SelectedLfo::Depth => 4,
+            SelectedLfo::Target => 5,
+        }
+    }
+}
+
+#[derive(PartialEq)]
+enum SelectedMod {
+    Source,
+    Destination,
+    Amount,
+}
+
+impl SelectedMod {
+    fn next(&mut self) {
+        *self = match self {
+            SelectedMod::Source => SelectedMod::Destination,
+            SelectedMod::Destination => SelectedMod::Amount,
+            SelectedMod::Amount => SelectedMod::Source,
+        }
+    }
+
+    fn prev(&mut self) {
+        *self = match self {
+            SelectedMod::Source => SelectedMod::Amount,
+            SelectedMod::Amount => SelectedMod::Destination,
+            SelectedMod::Destination => SelectedMod::Source,
+        }
+    }
+
+    /// Column of the parameter in a matrix line, after the label
+    fn position(&self) -> usize {
+        match self {
+            SelectedMod::Source => 1,
+            SelectedMod::Destination => 2,
+            SelectedMod::Amount => 3,
         }
     }
 }
@@ -291,22 +960,136 @@ impl Selected {
             Selected::Div => Selected::Dir,
             Selected::Dir => Selected::Pitch,
             Selected::Pitch => Selected::Gain,
-            Selected::Gain => Selected::Attack,
-            Selected::Attack => Selected::Release,
-            Selected::Release => Selected::Div,
+            Selected::Gain => Selected::VoiceMode,
+            Selected::VoiceMode => Selected::GrainSize,
+            Selected::GrainSize => Selected::GrainDensity,
+            Selected::GrainDensity => Selected::GrainPosition,
+            Selected::GrainPosition => Selected::PositionJitter,
+            Selected::PositionJitter => Selected::PitchJitter,
+            Selected::PitchJitter => Selected::GrainWindow,
+            Selected::GrainWindow => Selected::FilterMode,
+            Selected::FilterMode => Selected::Cutoff,
+            Selected::Cutoff => Selected::Resonance,
+            Selected::Resonance => Selected::FilterEnv,
+            Selected::FilterEnv => Selected::Mono,
+            Selected::Mono => Selected::Gate,
+            Selected::Gate => Selected::DelaySend,
+            Selected::DelaySend => Selected::ReverbSend,
+            Selected::ReverbSend => Selected::Attack,
+            Selected::Attack => Selected::Decay,
+            Selected::Decay => Selected::Sustain,
+            Selected::Sustain => Selected::Release,
+            Selected::Release => Selected::Curve,
+            Selected::Curve => Selected::Interpolation,
+            Selected::Interpolation => Selected::Fine,
+            Selected::Fine => Selected::Scale,
+            Selected::Scale => Selected::Reverse,
+            Selected::Reverse => Selected::ReverseProbability,
+            Selected::ReverseProbability => Selected::ScaleNote(0),
+            Selected::ScaleNote(note) => {
+                if *note + 1 >= SCALE_NOTES {
+                    Selected::PitchSequenceLength
+                } else {
+                    Selected::ScaleNote(*note + 1)
+                }
+            }
+            Selected::PitchSequenceLength => Selected::PitchSequenceStep(0),
+            Selected::PitchSequenceStep(step) => {
+                if *step + 1 >= PITCH_STEPS {
+                    Selected::Div
+                } else {
+                    Selected::PitchSequenceStep(*step + 1)
+                }
+            }
         };
     }
 
     fn prev(&mut self) {
         *self = match self {
-            Selected::Div => Selected::Release,
-            Selected::Release => Selected::Attack,
-            Selected::Attack => Selected::Gain,
+            Selected::Div => Selected::PitchSequenceStep(PITCH_STEPS - 1),
+            Selected::PitchSequenceStep(step) => {
+                if *step == 0 {
+                    Selected::PitchSequenceLength
+                } else {
+                    Selected::PitchSequenceStep(*step - 1)
+                }
+            }
+            Selected::PitchSequenceLength => Selected::ScaleNote(SCALE_NOTES - 1),
+            Selected::ScaleNote(note) => {
+                if *note == 0 {
+                    Selected::ReverseProbability
+                } else {
+                    Selected::ScaleNote(*note - 1)
+                }
+            }
+            Selected::ReverseProbability => Selected::Reverse,
+            Selected::Reverse => Selected::Scale,
+            Selected::Scale => Selected::Fine,
+            Selected::Fine => Selected::Interpolation,
+            Selected::Interpolation => Selected::Curve,
+            Selected::Curve => Selected::Release,
+            Selected::Release => Selected::Sustain,
+            Selected::Sustain => Selected::Decay,
+            Selected::Decay => Selected::Attack,
+            Selected::Attack => Selected::ReverbSend,
+            Selected::ReverbSend => Selected::DelaySend,
+            Selected::DelaySend => Selected::Gate,
+            Selected::Gate => Selected::Mono,
+            Selected::Mono => Selected::FilterEnv,
+            Selected::FilterEnv => Selected::Resonance,
+            Selected::Resonance => Selected::Cutoff,
+            Selected::Cutoff => Selected::FilterMode,
+            Selected::FilterMode => Selected::GrainWindow,
+            Selected::GrainWindow => Selected::PitchJitter,
+            Selected::PitchJitter => Selected::PositionJitter,
+            Selected::PositionJitter => Selected::GrainPosition,
+            Selected::GrainPosition => Selected::GrainDensity,
+            Selected::GrainDensity => Selected::GrainSize,
+            Selected::GrainSize => Selected::VoiceMode,
+            Selected::VoiceMode => Selected::Gain,
             Selected::Gain => Selected::Pitch,
             Selected::Pitch => Selected::Dir,
             Selected::Dir => Selected::Div,
         };
     }
+
+    /// Line and column of the parameter in a sequence's parameter block
+    fn position(&self) -> (usize, usize) {
+        match self {
+            Selected::Div => (0, 0),
+            Selected::Dir => (0, 1),
+            Selected::Pitch => (0, 2),
+            Selected::Gain => (0, 3),
+            Selected::VoiceMode => (1, 0),
+            Selected::GrainSize => (1, 1),
+            Selected::GrainDensity => (1, 2),
+            Selected::GrainPosition => (1, 3),
+            Selected::PositionJitter => (2, 0),
+            Selected::PitchJitter => (2, 1),
+            Selected::GrainWindow => (2, 2),
+            Selected::FilterMode => (3, 0),
+            Selected::Cutoff => (3, 1),
+            Selected::Resonance => (3, 2),
+            Selected::FilterEnv => (3, 3),
+            Selected::Mono => (4, 0),
+            Selected::Gate => (4, 1),
+            Selected::DelaySend => (4, 2),
+            Selected::ReverbSend => (4, 3),
+            Selected::Attack => (5, 0),
+            Selected::Decay => (5, 1),
+            Selected::Sustain => (5, 2),
+            Selected::Release => (5, 3),
+            Selected::Curve => (6, 0),
+            Selected::Interpolation => (6, 1),
+            Selected::Fine => (7, 0),
+            Selected::Scale => (7, 1),
+            Selected::Reverse => (7, 2),
+            Selected::ReverseProbability => (7, 3),
+            Selected::ScaleNote(note) => (8, *note as usize + 1),
+            Selected::PitchSequenceLength => (9, 0),
+            Selected::PitchSequenceStep(step) => (9, *step as usize + 1),
+        }
+    }
 }
 
 pub struct State {
@@ -314,10 +1097,13 @@ pub struct State {
     draw_data: Output<DrawData>,
     selected_area: SelectedArea,
     selected_global: SelectedGlobal,
+    selected_lfo: SelectedLfo,
+    selected_mod: SelectedMod,
     mode: Mode,
     sender: Sender<SetEvent>,
     selected: Selected,
-    semitones: Vec<i8>,
+    /// outcome of the last matrix save or load
+    matrix_status: Option<&'static str>,
 }
 
 impl State {
@@ -328,11 +1114,19 @@ impl State {
                 Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                     match key_event.code {
                         KeyCode::Esc => self.exiting = true,
+                        KeyCode::Char('S') => self.matrix_status = Some(save_matrix(draw_data)),
+                        KeyCode::Char('L') => self.matrix_status = Some(load_matrix(&self.sender)),
                         KeyCode::Char('m') => match self.mode {
                             Mode::Record => self.mode = Mode::Erase,
                             Mode::Erase => self.mode = Mode::RangeStart,
                             Mode::RangeStart => self.mode = Mode::RangeEnd,
-                            Mode::RangeEnd => self.mode = Mode::Record,
+                            Mode::RangeEnd => self.mode = Mode::Choke,
+                            Mode::Choke => self.mode = Mode::Capture,
+                            Mode::Capture => self.mode = Mode::Length,
+                            Mode::Length => self.mode = Mode::Reverse,
+                            Mode::Reverse => self.mode = Mode::Edit,
+                            Mode::Edit => self.mode = Mode::Undo,
+                            Mode::Undo => self.mode = Mode::Record,
                         },
                         KeyCode::Char('j') => {
                             self.selected_area.next();
@@ -342,24 +1136,80 @@ impl State {
                         }
                         KeyCode::Char('l') => match self.selected_area {
                             SelectedArea::Sequence(_) => self.selected.next(),
+                            SelectedArea::Lfo(_) => self.selected_lfo.next(),
+                            SelectedArea::Matrix(_) => self.selected_mod.next(),
                             SelectedArea::Global => self.selected_global.next(),
                         },
                         KeyCode::Char('h') => match self.selected_area {
                             SelectedArea::Sequence(_) => self.selected.prev(),
+                            SelectedArea::Lfo(_) => self.selected_lfo.prev(),
+                            SelectedArea::Matrix(_) => self.selected_mod.prev(),
                             SelectedArea::Global => self.selected_global.prev(),
                         },
                         KeyCode::Char('K') => match self.selected_area {
                             SelectedArea::Sequence(idx) => match self.selected {
-                                Selected::Pitch => {
-                                    let semitone = &mut self.semitones[idx];
-                                    if *semitone >= 12 {
-                                        *semitone = -12;
-                                    } else {
-                                        *semitone += 1;
-                                    }
-                                    self.sender.send(SetEvent::SetPitch((idx, *semitone)))
+                                Selected::Pitch => self
+                                    .sender
+                                    .send(SetEvent::SetPitch((
+                                        idx,
+                                        (draw_data.pitches[idx].semitones + 1).min(MAX_SEMITONES),
+                                    )))
+                                    .unwrap(),
+                                Selected::Fine => self
+                                    .sender
+                                    .send(SetEvent::SetFineTune((
+                                        idx,
+                                        (draw_data.pitches[idx].cents + 5).min(MAX_CENTS),
+                                    )))
+                                    .unwrap(),
+                                Selected::Scale => self
+                                    .sender
+                                    .send(SetEvent::SetScale((
+                                        idx,
+                                        draw_data.pitches[idx].scale.next(),
+                                    )))
+                                    .unwrap(),
+                                Selected::Reverse => self
+                                    .sender
+                                    .send(SetEvent::SetReverse((idx, !draw_data.reverses[idx])))
+                                    .unwrap(),
+                                Selected::ReverseProbability => {
+                                    let new_probability =
+                                        (draw_data.reverse_probabilities[idx] + 0.05).min(1.0);
+                                    self.sender
+                                        .send(SetEvent::SetReverseProbability((
+                                            idx,
+                                            new_probability,
+                                        )))
+                                        .unwrap();
+                                }
+                                Selected::ScaleNote(note) => self
+                                    .sender
+                                    .send(SetEvent::SetScale((
+                                        idx,
+                                        draw_data.pitches[idx].scale.toggle_note(note),
+                                    )))
+                                    .unwrap(),
+                                Selected::PitchSequenceLength => {
+                                    let pitch_sequence = &draw_data.pitch_sequences[idx];
+                                    self.sender
+                                        .send(SetEvent::SetPitchSequenceLength((
+                                            idx,
+                                            (pitch_sequence.length + 1).min(PITCH_STEPS),
+                                        )))
+                                        .unwrap();
+                                }
+                                Selected::PitchSequenceStep(step) => {
+                                    let pitch_sequence = &draw_data.pitch_sequences[idx];
+                                    self.sender
+                                        .send(SetEvent::SetPitchSequenceStep((
+                                            idx,
+                                            step as usize,
+                                            (pitch_sequence.offsets[step as usize] + 1)
+                                                .min(MAX_SEMITONES),
+                                        )))
+                                        .unwrap();
                                 }
-                                .unwrap(),
                                 Selected::Div => self
                                     .sender
                                     .send(SetEvent::SetSubdivision((
@@ -401,209 +1251,1077 @@ impl State {
                                         .send(SetEvent::SetRelease((idx, new_rel)))
                                         .unwrap();
                                 }
-                            },
-                            SelectedArea::Global => match self.selected_global {
-                                SelectedGlobal::Bpm => self
+                                Selected::VoiceMode => self
                                     .sender
-                                    .send(SetEvent::SetBmp(draw_data.bpm + 1.0))
+                                    .send(SetEvent::SetVoiceMode((
+                                        idx,
+                                        draw_data.voice_modes[idx].next(),
+                                    )))
                                     .unwrap(),
-                            },
-                        },
-                        KeyCode::Char('J') => match self.selected_area {
-                            SelectedArea::Sequence(idx) => match self.selected {
-                                Selected::Pitch => {
-                                    let semitone = &mut self.semitones[idx];
-                                    if *semitone <= -12 {
-                                        *semitone = 12;
-                                    } else {
-                                        *semitone -= 1;
-                                    }
-                                    self.sender.send(SetEvent::SetPitch((idx, *semitone)))
+                                Selected::GrainSize => {
+                                    let new_size = (draw_data.grains[idx].size + 0.01).min(0.5);
+                                    self.sender
+                                        .send(SetEvent::SetGrainSize((idx, new_size)))
+                                        .unwrap();
                                 }
-                                .unwrap(),
-                                Selected::Div => self
+                                Selected::GrainDensity => {
+                                    let new_density =
+                                        (draw_data.grains[idx].density + 1.0).min(100.0);
+                                    self.sender
+                                        .send(SetEvent::SetGrainDensity((idx, new_density)))
+                                        .unwrap();
+                                }
+                                Selected::GrainPosition => {
+                                    let new_pos = (draw_data.grains[idx].position + 0.05).min(1.0);
+                                    self.sender
+                                        .send(SetEvent::SetGrainPosition((idx, new_pos)))
+                                        .unwrap();
+                                }
+                                Selected::PositionJitter => {
+                                    let new_jitter =
+                                        (draw_data.grains[idx].position_jitter + 0.05).min(1.0);
+                                    self.sender
+                                        .send(SetEvent::SetPositionJitter((idx, new_jitter)))
+                                        .unwrap();
+                                }
+                                Selected::PitchJitter => {
+                                    let new_jitter =
+                                        (draw_data.grains[idx].pitch_jitter + 0.5).min(12.0);
+                                    self.sender
+                                        .send(SetEvent::SetPitchJitter((idx, new_jitter)))
+                                        .unwrap();
+                                }
+                                Selected::GrainWindow => self
                                     .sender
-                                    .send(SetEvent::SetSubdivision((
+                                    .send(SetEvent::SetGrainWindow((
                                         idx,
-                                        draw_data.subdivisions[idx].prev(),
+                                        draw_data.grains[idx].window.next(),
                                     )))
                                     .unwrap(),
-                                Selected::Dir => self
+                                Selected::FilterMode => self
                                     .sender
-                                    .send(SetEvent::SetDir((idx, draw_data.dirs[idx].prev())))
+                                    .send(SetEvent::SetFilterMode((
+                                        idx,
+                                        draw_data.filter_modes[idx].next(),
+                                    )))
                                     .unwrap(),
-                                Selected::Gain => {
-                                    let mut new_gain = draw_data.gains[idx] - 0.1;
-                                    if new_gain < 0.0 {
-                                        new_gain = 0.0
-                                    }
-
+                                Selected::Cutoff => {
+                                    let new_cutoff = (draw_data.cutoffs[idx] * 1.25).min(20000.0);
                                     self.sender
-                                        .send(SetEvent::SetGain((idx, new_gain)))
+                                        .send(SetEvent::SetCutoff((idx, new_cutoff)))
                                         .unwrap();
                                 }
-                                Selected::Attack => {
-                                    let mut new_att = draw_data.attacks[idx] - 0.1;
-                                    if new_att < 0.1 {
-                                        new_att = 0.1
-                                    }
-
+                                Selected::Resonance => {
+                                    let new_res = (draw_data.resonances[idx] + 0.1).min(1.0);
                                     self.sender
-                                        .send(SetEvent::SetAttack((idx, new_att)))
+                                        .send(SetEvent::SetResonance((idx, new_res)))
                                         .unwrap();
                                 }
-                                Selected::Release => {
-                                    let mut new_rel = draw_data.releases[idx] - 0.1;
-                                    if new_rel < 0.1 {
-                                        new_rel = 0.1
-                                    }
+                                Selected::FilterEnv => {
+                                    let new_env = (draw_data.filter_envs[idx] + 0.1).min(1.0);
+                                    self.sender
+                                        .send(SetEvent::SetFilterEnv((idx, new_env)))
+                                        .unwrap();
+                                }
+                                Selected::DelaySend => {
+                                    let new_send = (draw_data.delay_sends[idx] + 0.1).min(1.0);
+                                    self.sender
+                                        .send(SetEvent::SetDelaySend((idx, new_send)))
+                                        .unwrap();
+                                }
+                                Selected::ReverbSend => {
+                                    let new_send = (draw_data.reverb_sends[idx] + 0.1).min(1.0);
+                                    self.sender
+                                        .send(SetEvent::SetReverbSend((idx, new_send)))
+                                        .unwrap();
+                                }
+                                Selected::Mono => self
+                                    .sender
+                                    .send(SetEvent::SetMono((idx, !draw_data.monos[idx])))
+                                    .unwrap(),
+                                Selected::Decay => {
+                                    let new_dec = (draw_data.decays[idx] + 0.1).min(1.0);
+                                    self.sender
+                                        .send(SetEvent::SetDecay((idx, new_dec)))
+                                        .unwrap();
+                                }
+                                Selected::Sustain => {
+                                    let new_sus = (draw_data.sustains[idx] + 0.1).min(1.0);
+                                    self.sender
+                                        .send(SetEvent::SetSustain((idx, new_sus)))
+                                        .unwrap();
+                                }
+                                Selected::Gate => {
+                                    let new_gate = match draw_data.gates[idx] {
+                                        GateLength::Fraction(fraction) if fraction >= 4.0 => {
+                                            GateLength::Ms(10.0)
+                                        }
+                                        GateLength::Fraction(fraction) => {
+                                            GateLength::Fraction(fraction + 0.25)
+                                        }
+                                        GateLength::Ms(ms) => {
+                                            GateLength::Ms((ms + 10.0).min(2000.0))
+                                        }
+                                    };
+                                    self.sender
+                                        .send(SetEvent::SetGate((idx, new_gate)))
+                                        .unwrap();
+                                }
+                                Selected::Curve => self
+                                    .sender
+                                    .send(SetEvent::SetCurve((idx, draw_data.curves[idx].next())))
+                                    .unwrap(),
+                                Selected::Interpolation => self
+                                    .sender
+                                    .send(SetEvent::SetInterpolation((
+                                        idx,
+                                        draw_data.interpolations[idx].next(),
+                                    )))
+                                    .unwrap(),
+                            },
+                            SelectedArea::Lfo(idx) => match self.selected_lfo {
+                                SelectedLfo::Shape => self
+                                    .sender
+                                    .send(SetEvent::SetLfoShape((
+                                        idx,
+                                        draw_data.lfo_shapes[idx].next(),
+                                    )))
+                                    .unwrap(),
+                                SelectedLfo::RateMode => {
+                                    let new_rate = match draw_data.lfo_rates[idx] {
+                                        LfoRate::Sync(_) => LfoRate::Hz(1.0),
+                                        LfoRate::Hz(_) => LfoRate::Sync(Subdivision::Quarter),
+                                    };
+                                    self.sender
+                                        .send(SetEvent::SetLfoRate((idx, new_rate)))
+                                        .unwrap();
+                                }
+                                SelectedLfo::Rate => {
+                                    let new_rate = match draw_data.lfo_rates[idx] {
+                                        LfoRate::Sync(subdivision) => {
+                                            LfoRate::Sync(subdivision.next())
+                                        }
+                                        LfoRate::Hz(hz) => LfoRate::Hz((hz * 1.25).min(20.0)),
+                                    };
+                                    self.sender
+                                        .send(SetEvent::SetLfoRate((idx, new_rate)))
+                                        .unwrap();
+                                }
+                                SelectedLfo::Depth => {
+                                    let new_depth = (draw_data.lfo_depths[idx] + 0.1).min(1.0);
+                                    self.sender
+                                        .send(SetEvent::SetLfoDepth((idx, new_depth)))
+                                        .unwrap();
+                                }
+                                SelectedLfo::Target => self
+                                    .sender
+                                    .send(SetEvent::SetLfoTarget((
+                                        idx,
+                                        step_mod_target(draw_data.lfo_targets[idx], true),
+                                    )))
+                                    .unwrap(),
+                            },
+                            SelectedArea::Matrix(idx) => match self.selected_mod {
+                                SelectedMod::Source => self
+                                    .sender
+                                    .send(SetEvent::SetModSource((
+                                        idx,
+                                        draw_data.mod_sources[idx].next(),
+                                    )))
+                                    .unwrap(),
+                                SelectedMod::Destination => self
+                                    .sender
+                                    .send(SetEvent::SetModDestination((
+                                        idx,
+                                        step_mod_target(draw_data.mod_destinations[idx], true),
+                                    )))
+                                    .unwrap(),
+                                SelectedMod::Amount => {
+                                    let new_amount = (draw_data.mod_amounts[idx] + 0.1).min(1.0);
+                                    self.sender
+                                        .send(SetEvent::SetModAmount((idx, new_amount)))
+                                        .unwrap();
+                                }
+                            },
+                            SelectedArea::Global => match self.selected_global {
+                                SelectedGlobal::Bpm => self
+                                    .sender
+                                    .send(SetEvent::SetBmp(draw_data.bpm + 1.0))
+                                    .unwrap(),
+                                SelectedGlobal::DelayTime => self
+                                    .sender
+                                    .send(SetEvent::SetDelayTime(
+                                        draw_data.delay_subdivision.next(),
+                                    ))
+                                    .unwrap(),
+                                SelectedGlobal::DelayFeedback => {
+                                    let new_fb = (draw_data.delay_feedback + 0.1).min(0.9);
+                                    self.sender
+                                        .send(SetEvent::SetDelayFeedback(new_fb))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::ReverbSize => {
+                                    let new_size = (draw_data.reverb_size + 0.1).min(1.0);
+                                    self.sender.send(SetEvent::SetReverbSize(new_size)).unwrap();
+                                }
+                                SelectedGlobal::ReverbDamping => {
+                                    let new_damp = (draw_data.reverb_damping + 0.1).min(1.0);
+                                    self.sender
+                                        .send(SetEvent::SetReverbDamping(new_damp))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::MasterGain => {
+                                    let new_gain = (draw_data.master_gain + 0.1).min(2.0);
+                                    self.sender.send(SetEvent::SetMasterGain(new_gain)).unwrap();
+                                }
+                                SelectedGlobal::Limiter => {
+                                    self.sender.send(SetEvent::ToggleLimiter).unwrap()
+                                }
+                                SelectedGlobal::VoiceStealing => self
+                                    .sender
+                                    .send(SetEvent::SetVoiceStealing(
+                                        draw_data.voice_stealing.next(),
+                                    ))
+                                    .unwrap(),
+                                SelectedGlobal::Polyphony => {
+                                    let new_polyphony =
+                                        (draw_data.polyphony + 1).min(MAX_POLYPHONY as usize);
+                                    self.sender
+                                        .send(SetEvent::SetPolyphony(new_polyphony))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::InputGain => {
+                                    let new_gain = (draw_data.input_gain + 0.1).min(4.0);
+                                    self.sender.send(SetEvent::SetInputGain(new_gain)).unwrap();
+                                }
+                                SelectedGlobal::Monitor => {
+                                    let new_level = (draw_data.monitor_level + 0.1).min(1.0);
+                                    self.sender
+                                        .send(SetEvent::SetMonitorLevel(new_level))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::RecordSource => self
+                                    .sender
+                                    .send(SetEvent::SetRecordSource(draw_data.record_source.next()))
+                                    .unwrap(),
+                                SelectedGlobal::CaptureLength => self
+                                    .sender
+                                    .send(SetEvent::SetCaptureLength(
+                                        draw_data.capture_length.toggle(),
+                                    ))
+                                    .unwrap(),
+                                SelectedGlobal::RecordArm => self
+                                    .sender
+                                    .send(SetEvent::SetRecordArm(draw_data.record_arm.next()))
+                                    .unwrap(),
+                                SelectedGlobal::RecordThreshold => {
+                                    let new_threshold =
+                                        (draw_data.record_threshold + 0.05).min(1.0);
+                                    self.sender
+                                        .send(SetEvent::SetRecordThreshold(new_threshold))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::RecordMode => self
+                                    .sender
+                                    .send(SetEvent::SetRecordMode(draw_data.record_mode.toggle()))
+                                    .unwrap(),
+                                SelectedGlobal::OverdubFeedback => {
+                                    let new_feedback = (draw_data.overdub_feedback + 0.05).min(1.0);
+                                    self.sender
+                                        .send(SetEvent::SetOverdubFeedback(new_feedback))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::Processing => {
+                                    self.sender.send(SetEvent::ToggleProcessing).unwrap()
+                                }
+                                SelectedGlobal::TrimThreshold => {
+                                    let new_threshold =
+                                        (draw_data.processing.trim_threshold + 2.0).min(0.0);
+                                    self.sender
+                                        .send(SetEvent::SetTrimThreshold(new_threshold))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::TargetPeak => {
+                                    let new_peak =
+                                        (draw_data.processing.target_peak + 0.5).min(0.0);
+                                    self.sender.send(SetEvent::SetTargetPeak(new_peak)).unwrap();
+                                }
+                                SelectedGlobal::EditKind => self
+                                    .sender
+                                    .send(SetEvent::SetEditKind(draw_data.edit_kind.next()))
+                                    .unwrap(),
+                                SelectedGlobal::EditGain => {
+                                    let new_gain = (draw_data.edit_gain + 1.0).min(24.0);
+                                    self.sender.send(SetEvent::SetEditGain(new_gain)).unwrap();
+                                }
+                                SelectedGlobal::EditFade => {
+                                    let new_fade = (draw_data.edit_fade + 0.01).min(2.0);
+                                    self.sender.send(SetEvent::SetEditFade(new_fade)).unwrap();
+                                }
+                                SelectedGlobal::CropStart => {
+                                    let new_start =
+                                        (draw_data.crop.0 + 0.05).clamp(0.0, draw_data.crop.1);
+                                    self.sender
+                                        .send(SetEvent::SetCrop((new_start, draw_data.crop.1)))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::CropEnd => {
+                                    let new_end =
+                                        (draw_data.crop.1 + 0.05).clamp(draw_data.crop.0, 1.0);
+                                    self.sender
+                                        .send(SetEvent::SetCrop((draw_data.crop.0, new_end)))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::DuplicateTarget => self
+                                    .sender
+                                    .send(SetEvent::SetDuplicateTarget(
+                                        (draw_data.duplicate_target + 1) % STEP_NUM as usize,
+                                    ))
+                                    .unwrap(),
+                            },
+                        },
+                        KeyCode::Char('J') => match self.selected_area {
+                            SelectedArea::Sequence(idx) => match self.selected {
+                                Selected::Pitch => self
+                                    .sender
+                                    .send(SetEvent::SetPitch((
+                                        idx,
+                                        (draw_data.pitches[idx].semitones - 1).max(-MAX_SEMITONES),
+                                    )))
+                                    .unwrap(),
+                                Selected::Fine => self
+                                    .sender
+                                    .send(SetEvent::SetFineTune((
+                                        idx,
+                                        (draw_data.pitches[idx].cents - 5).max(-MAX_CENTS),
+                                    )))
+                                    .unwrap(),
+                                Selected::Scale => self
+                                    .sender
+                                    .send(SetEvent::SetScale((
+                                        idx,
+                                        draw_data.pitches[idx].scale.prev(),
+                                    )))
+                                    .unwrap(),
+                                Selected::Reverse => self
+                                    .sender
+                                    .send(SetEvent::SetReverse((idx, !draw_data.reverses[idx])))
+                                    .unwrap(),
+                                Selected::ReverseProbability => {
+                                    let new_probability =
+                                        (draw_data.reverse_probabilities[idx] - 0.05).max(0.0);
+                                    self.sender
+                                        .send(SetEvent::SetReverseProbability((
+                                            idx,
+                                            new_probability,
+                                        )))
+                                        .unwrap();
+                                }
+                                Selected::ScaleNote(note) => self
+                                    .sender
+                                    .send(SetEvent::SetScale((
+                                        idx,
+                                        draw_data.pitches[idx].scale.toggle_note(note),
+                                    )))
+                                    .unwrap(),
+                                Selected::PitchSequenceLength => {
+                                    let pitch_sequence = &draw_data.pitch_sequences[idx];
+                                    self.sender
+                                        .send(SetEvent::SetPitchSequenceLength((
+                                            idx,
+                                            pitch_sequence.length.saturating_sub(1).max(1),
+                                        )))
+                                        .unwrap();
+                                }
+                                Selected::PitchSequenceStep(step) => {
+                                    let pitch_sequence = &draw_data.pitch_sequences[idx];
+                                    self.sender
+                                        .send(SetEvent::SetPitchSequenceStep((
+                                            idx,
+                                            step as usize,
+                                            (pitch_sequence.offsets[step as usize] - 1)
+                                                .max(-MAX_SEMITONES),
+                                        )))
+                                        .unwrap();
+                                }
+                                Selected::Div => self
+                                    .sender
+                                    .send(SetEvent::SetSubdivision((
+                                        idx,
+                                        draw_data.subdivisions[idx].prev(),
+                                    )))
+                                    .unwrap(),
+                                Selected::Dir => self
+                                    .sender
+                                    .send(SetEvent::SetDir((idx, draw_data.dirs[idx].prev())))
+                                    .unwrap(),
+                                Selected::Gain => {
+                                    let mut new_gain = draw_data.gains[idx] - 0.1;
+                                    if new_gain < 0.0 {
+                                        new_gain = 0.0
+                                    }
+
+                                    self.sender
+                                        .send(SetEvent::SetGain((idx, new_gain)))
+                                        .unwrap();
+                                }
+                                Selected::Attack => {
+                                    let mut new_att = draw_data.attacks[idx] - 0.1;
+                                    if new_att < 0.1 {
+                                        new_att = 0.1
+                                    }
+
+                                    self.sender
+                                        .send(SetEvent::SetAttack((idx, new_att)))
+                                        .unwrap();
+                                }
+                                Selected::Release => {
+                                    let mut new_rel = draw_data.releases[idx] - 0.1;
+                                    if new_rel < 0.1 {
+                                        new_rel = 0.1
+                                    }
 
                                     self.sender
                                         .send(SetEvent::SetRelease((idx, new_rel)))
                                         .unwrap();
                                 }
+                                Selected::VoiceMode => self
+                                    .sender
+                                    .send(SetEvent::SetVoiceMode((
+                                        idx,
+                                        draw_data.voice_modes[idx].prev(),
+                                    )))
+                                    .unwrap(),
+                                Selected::GrainSize => {
+                                    let new_size = (draw_data.grains[idx].size - 0.01).max(0.01);
+                                    self.sender
+                                        .send(SetEvent::SetGrainSize((idx, new_size)))
+                                        .unwrap();
+                                }
+                                Selected::GrainDensity => {
+                                    let new_density =
+                                        (draw_data.grains[idx].density - 1.0).max(1.0);
+                                    self.sender
+                                        .send(SetEvent::SetGrainDensity((idx, new_density)))
+                                        .unwrap();
+                                }
+                                Selected::GrainPosition => {
+                                    let new_pos = (draw_data.grains[idx].position - 0.05).max(0.0);
+                                    self.sender
+                                        .send(SetEvent::SetGrainPosition((idx, new_pos)))
+                                        .unwrap();
+                                }
+                                Selected::PositionJitter => {
+                                    let new_jitter =
+                                        (draw_data.grains[idx].position_jitter - 0.05).max(0.0);
+                                    self.sender
+                                        .send(SetEvent::SetPositionJitter((idx, new_jitter)))
+                                        .unwrap();
+                                }
+                                Selected::PitchJitter => {
+                                    let new_jitter =
+                                        (draw_data.grains[idx].pitch_jitter - 0.5).max(0.0);
+                                    self.sender
+                                        .send(SetEvent::SetPitchJitter((idx, new_jitter)))
+                                        .unwrap();
+                                }
+                                Selected::GrainWindow => self
+                                    .sender
+                                    .send(SetEvent::SetGrainWindow((
+                                        idx,
+                                        draw_data.grains[idx].window.prev(),
+                                    )))
+                                    .unwrap(),
+                                Selected::FilterMode => self
+                                    .sender
+                                    .send(SetEvent::SetFilterMode((
+                                        idx,
+                                        draw_data.filter_modes[idx].prev(),
+                                    )))
+                                    .unwrap(),
+                                Selected::Cutoff => {
+                                    let new_cutoff = (draw_data.cutoffs[idx] / 1.25).max(20.0);
+                                    self.sender
+                                        .send(SetEvent::SetCutoff((idx, new_cutoff)))
+                                        .unwrap();
+                                }
+                                Selected::Resonance => {
+                                    let new_res = (draw_data.resonances[idx] - 0.1).max(0.0);
+                                    self.sender
+                                        .send(SetEvent::SetResonance((idx, new_res)))
+                                        .unwrap();
+                                }
+                                Selected::FilterEnv => {
+                                    let new_env = (draw_data.filter_envs[idx] - 0.1).max(-1.0);
+                                    self.sender
+                                        .send(SetEvent::SetFilterEnv((idx, new_env)))
+                                        .unwrap();
+                                }
+                                Selected::DelaySend => {
+                                    let new_send = (draw_data.delay_sends[idx] - 0.1).max(0.0);
+                                    self.sender
+                                        .send(SetEvent::SetDelaySend((idx, new_send)))
+                                        .unwrap();
+                                }
+                                Selected::ReverbSend => {
+                                    let new_send = (draw_data.reverb_sends[idx] - 0.1).max(0.0);
+                                    self.sender
+                                        .send(SetEvent::SetReverbSend((idx, new_send)))
+                                        .unwrap();
+                                }
+                                Selected::Mono => self
+                                    .sender
+                                    .send(SetEvent::SetMono((idx, !draw_data.monos[idx])))
+                                    .unwrap(),
+                                Selected::Decay => {
+                                    let new_dec = (draw_data.decays[idx] - 0.1).max(0.0);
+                                    self.sender
+                                        .send(SetEvent::SetDecay((idx, new_dec)))
+                                        .unwrap();
+                                }
+                                Selected::Sustain => {
+                                    let new_sus = (draw_data.sustains[idx] - 0.1).max(0.0);
+                                    self.sender
+                                        .send(SetEvent::SetSustain((idx, new_sus)))
+                                        .unwrap();
+                                }
+                                Selected::Gate => {
+                                    let new_gate = match draw_data.gates[idx] {
+                                        GateLength::Ms(ms) if ms <= 10.0 => {
+                                            GateLength::Fraction(4.0)
+                                        }
+                                        GateLength::Ms(ms) => GateLength::Ms(ms - 10.0),
+                                        GateLength::Fraction(fraction) => {
+                                            GateLength::Fraction((fraction - 0.25).max(0.25))
+                                        }
+                                    };
+                                    self.sender
+                                        .send(SetEvent::SetGate((idx, new_gate)))
+                                        .unwrap();
+                                }
+                                Selected::Curve => self
+                                    .sender
+                                    .send(SetEvent::SetCurve((idx, draw_data.curves[idx].prev())))
+                                    .unwrap(),
+                                Selected::Interpolation => self
+                                    .sender
+                                    .send(SetEvent::SetInterpolation((
+                                        idx,
+                                        draw_data.interpolations[idx].prev(),
+                                    )))
+                                    .unwrap(),
+                            },
+                            SelectedArea::Lfo(idx) => match self.selected_lfo {
+                                SelectedLfo::Shape => self
+                                    .sender
+                                    .send(SetEvent::SetLfoShape((
+                                        idx,
+                                        draw_data.lfo_shapes[idx].prev(),
+                                    )))
+                                    .unwrap(),
+                                SelectedLfo::RateMode => {
+                                    let new_rate = match draw_data.lfo_rates[idx] {
+                                        LfoRate::Sync(_) => LfoRate::Hz(1.0),
+                                        LfoRate::Hz(_) => LfoRate::Sync(Subdivision::Quarter),
+                                    };
+                                    self.sender
+                                        .send(SetEvent::SetLfoRate((idx, new_rate)))
+                                        .unwrap();
+                                }
+                                SelectedLfo::Rate => {
+                                    let new_rate = match draw_data.lfo_rates[idx] {
+                                        LfoRate::Sync(subdivision) => {
+                                            LfoRate::Sync(subdivision.prev())
+                                        }
+                                        LfoRate::Hz(hz) => LfoRate::Hz((hz / 1.25).max(0.05)),
+                                    };
+                                    self.sender
+                                        .send(SetEvent::SetLfoRate((idx, new_rate)))
+                                        .unwrap();
+                                }
+                                SelectedLfo::Depth => {
+                                    let new_depth = (draw_data.lfo_depths[idx] - 0.1).max(0.0);
+                                    self.sender
+                                        .send(SetEvent::SetLfoDepth((idx, new_depth)))
+                                        .unwrap();
+                                }
+                                SelectedLfo::Target => self
+                                    .sender
+                                    .send(SetEvent::SetLfoTarget((
+                                        idx,
+                                        step_mod_target(draw_data.lfo_targets[idx], false),
+                                    )))
+                                    .unwrap(),
+                            },
+                            SelectedArea::Matrix(idx) => match self.selected_mod {
+                                SelectedMod::Source => self
+                                    .sender
+                                    .send(SetEvent::SetModSource((
+                                        idx,
+                                        draw_data.mod_sources[idx].prev(),
+                                    )))
+                                    .unwrap(),
+                                SelectedMod::Destination => self
+                                    .sender
+                                    .send(SetEvent::SetModDestination((
+                                        idx,
+                                        step_mod_target(draw_data.mod_destinations[idx], false),
+                                    )))
+                                    .unwrap(),
+                                SelectedMod::Amount => {
+                                    let new_amount = (draw_data.mod_amounts[idx] - 0.1).max(-1.0);
+                                    self.sender
+                                        .send(SetEvent::SetModAmount((idx, new_amount)))
+                                        .unwrap();
+                                }
                             },
                             SelectedArea::Global => match self.selected_global {
                                 SelectedGlobal::Bpm => self
                                     .sender
                                     .send(SetEvent::SetBmp(draw_data.bpm - 1.0))
                                     .unwrap(),
+                                SelectedGlobal::DelayTime => self
+                                    .sender
+                                    .send(SetEvent::SetDelayTime(
+                                        draw_data.delay_subdivision.prev(),
+                                    ))
+                                    .unwrap(),
+                                SelectedGlobal::DelayFeedback => {
+                                    let new_fb = (draw_data.delay_feedback - 0.1).max(0.0);
+                                    self.sender
+                                        .send(SetEvent::SetDelayFeedback(new_fb))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::ReverbSize => {
+                                    let new_size = (draw_data.reverb_size - 0.1).max(0.0);
+                                    self.sender.send(SetEvent::SetReverbSize(new_size)).unwrap();
+                                }
+                                SelectedGlobal::ReverbDamping => {
+                                    let new_damp = (draw_data.reverb_damping - 0.1).max(0.0);
+                                    self.sender
+                                        .send(SetEvent::SetReverbDamping(new_damp))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::MasterGain => {
+                                    let new_gain = (draw_data.master_gain - 0.1).max(0.0);
+                                    self.sender.send(SetEvent::SetMasterGain(new_gain)).unwrap();
+                                }
+                                SelectedGlobal::Limiter => {
+                                    self.sender.send(SetEvent::ToggleLimiter).unwrap()
+                                }
+                                SelectedGlobal::VoiceStealing => self
+                                    .sender
+                                    .send(SetEvent::SetVoiceStealing(
+                                        draw_data.voice_stealing.prev(),
+                                    ))
+                                    .unwrap(),
+                                SelectedGlobal::Polyphony => {
+                                    let new_polyphony =
+                                        draw_data.polyphony.saturating_sub(1).max(1);
+                                    self.sender
+                                        .send(SetEvent::SetPolyphony(new_polyphony))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::InputGain => {
+                                    let new_gain = (draw_data.input_gain - 0.1).max(0.0);
+                                    self.sender.send(SetEvent::SetInputGain(new_gain)).unwrap();
+                                }
+                                SelectedGlobal::Monitor => {
+                                    let new_level = (draw_data.monitor_level - 0.1).max(0.0);
+                                    self.sender
+                                        .send(SetEvent::SetMonitorLevel(new_level))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::RecordSource => self
+                                    .sender
+                                    .send(SetEvent::SetRecordSource(draw_data.record_source.prev()))
+                                    .unwrap(),
+                                SelectedGlobal::CaptureLength => self
+                                    .sender
+                                    .send(SetEvent::SetCaptureLength(
+                                        draw_data.capture_length.toggle(),
+                                    ))
+                                    .unwrap(),
+                                SelectedGlobal::RecordArm => self
+                                    .sender
+                                    .send(SetEvent::SetRecordArm(draw_data.record_arm.prev()))
+                                    .unwrap(),
+                                SelectedGlobal::RecordThreshold => {
+                                    let new_threshold =
+                                        (draw_data.record_threshold - 0.05).max(0.01);
+                                    self.sender
+                                        .send(SetEvent::SetRecordThreshold(new_threshold))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::RecordMode => self
+                                    .sender
+                                    .send(SetEvent::SetRecordMode(draw_data.record_mode.toggle()))
+                                    .unwrap(),
+                                SelectedGlobal::OverdubFeedback => {
+                                    let new_feedback = (draw_data.overdub_feedback - 0.05).max(0.0);
+                                    self.sender
+                                        .send(SetEvent::SetOverdubFeedback(new_feedback))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::Processing => {
+                                    self.sender.send(SetEvent::ToggleProcessing).unwrap()
+                                }
+                                SelectedGlobal::TrimThreshold => {
+                                    let new_threshold =
+                                        (draw_data.processing.trim_threshold - 2.0).max(-80.0);
+                                    self.sender
+                                        .send(SetEvent::SetTrimThreshold(new_threshold))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::TargetPeak => {
+                                    let new_peak =
+                                        (draw_data.processing.target_peak - 0.5).max(-24.0);
+                                    self.sender.send(SetEvent::SetTargetPeak(new_peak)).unwrap();
+                                }
+                                SelectedGlobal::EditKind => self
+                                    .sender
+                                    .send(SetEvent::SetEditKind(draw_data.edit_kind.prev()))
+                                    .unwrap(),
+                                SelectedGlobal::EditGain => {
+                                    let new_gain = (draw_data.edit_gain - 1.0).max(-24.0);
+                                    self.sender.send(SetEvent::SetEditGain(new_gain)).unwrap();
+                                }
+                                SelectedGlobal::EditFade => {
+                                    let new_fade = (draw_data.edit_fade - 0.01).max(0.01);
+                                    self.sender.send(SetEvent::SetEditFade(new_fade)).unwrap();
+                                }
+                                SelectedGlobal::CropStart => {
+                                    let new_start =
+                                        (draw_data.crop.0 - 0.05).clamp(0.0, draw_data.crop.1);
+                                    self.sender
+                                        .send(SetEvent::SetCrop((new_start, draw_data.crop.1)))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::CropEnd => {
+                                    let new_end =
+                                        (draw_data.crop.1 - 0.05).clamp(draw_data.crop.0, 1.0);
+                                    self.sender
+                                        .send(SetEvent::SetCrop((draw_data.crop.0, new_end)))
+                                        .unwrap();
+                                }
+                                SelectedGlobal::DuplicateTarget => self
+                                    .sender
+                                    .send(SetEvent::SetDuplicateTarget(
+                                        (draw_data.duplicate_target + STEP_NUM as usize - 1)
+                                            % STEP_NUM as usize,
+                                    ))
+                                    .unwrap(),
                             },
                         },
                         KeyCode::Char('1') => match self.mode {
                             Mode::Record => self.sender.send(SetEvent::Record(0)).unwrap(),
                             Mode::Erase => self.sender.send(SetEvent::Erase(0)).unwrap(),
-                            Mode::RangeStart => match self.selected_area {
-                                SelectedArea::Sequence(idx) => {
+                            Mode::RangeStart => {
+                                if let SelectedArea::Sequence(idx) = self.selected_area {
                                     self.sender.send(SetEvent::SetRangeStart((idx, 0))).unwrap()
                                 }
-                                _ => {}
-                            },
-                            Mode::RangeEnd => match self.selected_area {
-                                SelectedArea::Sequence(idx) => {
+                            }
+                            Mode::RangeEnd => {
+                                if let SelectedArea::Sequence(idx) = self.selected_area {
                                     self.sender.send(SetEvent::SetRangeEnd((idx, 0))).unwrap()
                                 }
-                                _ => {}
-                            },
+                            }
+                            Mode::Choke => {
+                                let group = match draw_data.choke_groups[0] {
+                                    None => Some(0),
+                                    Some(group) if group + 1 < CHOKE_GROUPS => Some(group + 1),
+                                    Some(_) => None,
+                                };
+                                self.sender
+                                    .send(SetEvent::SetChokeGroup((0, group)))
+                                    .unwrap()
+                            }
+                            Mode::Capture => self.sender.send(SetEvent::Capture(0)).unwrap(),
+                            Mode::Length => self
+                                .sender
+                                .send(SetEvent::SetRecordLength((
+                                    0,
+                                    draw_data.record_lengths[0].next(),
+                                )))
+                                .unwrap(),
+                            Mode::Reverse => self
+                                .sender
+                                .send(SetEvent::SetStepReverse((0, !draw_data.step_reverses[0])))
+                                .unwrap(),
+                            Mode::Edit => self.sender.send(SetEvent::Edit(0)).unwrap(),
+                            Mode::Undo => self.sender.send(SetEvent::Undo(0)).unwrap(),
                         },
                         KeyCode::Char('2') => match self.mode {
                             Mode::Record => self.sender.send(SetEvent::Record(1)).unwrap(),
                             Mode::Erase => self.sender.send(SetEvent::Erase(1)).unwrap(),
-                            Mode::RangeStart => match self.selected_area {
-                                SelectedArea::Sequence(idx) => {
+                            Mode::RangeStart => {
+                                if let SelectedArea::Sequence(idx) = self.selected_area {
                                     self.sender.send(SetEvent::SetRangeStart((idx, 1))).unwrap()
                                 }
-                                _ => {}
-                            },
-                            Mode::RangeEnd => match self.selected_area {
-                                SelectedArea::Sequence(idx) => {
+                            }
+                            Mode::RangeEnd => {
+                                if let SelectedArea::Sequence(idx) = self.selected_area {
                                     self.sender.send(SetEvent::SetRangeEnd((idx, 1))).unwrap()
                                 }
-                                _ => {}
-                            },
+                            }
+                            Mode::Choke => {
+                                let group = match draw_data.choke_groups[1] {
+                                    None => Some(0),
+                                    Some(group) if group + 1 < CHOKE_GROUPS => Some(group + 1),
+                                    Some(_) => None,
+                                };
+                                self.sender
+                                    .send(SetEvent::SetChokeGroup((1, group)))
+                                    .unwrap()
+                            }
+                            Mode::Capture => self.sender.send(SetEvent::Capture(1)).unwrap(),
+                            Mode::Length => self
+                                .sender
+                                .send(SetEvent::SetRecordLength((
+                                    1,
+                                    draw_data.record_lengths[1].next(),
+                                )))
+                                .unwrap(),
+                            Mode::Reverse => self
+                                .sender
+                                .send(SetEvent::SetStepReverse((1, !draw_data.step_reverses[1])))
+                                .unwrap(),
+                            Mode::Edit => self.sender.send(SetEvent::Edit(1)).unwrap(),
+                            Mode::Undo => self.sender.send(SetEvent::Undo(1)).unwrap(),
                         },
                         KeyCode::Char('3') => match self.mode {
                             Mode::Record => self.sender.send(SetEvent::Record(2)).unwrap(),
                             Mode::Erase => self.sender.send(SetEvent::Erase(2)).unwrap(),
-                            Mode::RangeStart => match self.selected_area {
-                                SelectedArea::Sequence(idx) => {
+                            Mode::RangeStart => {
+                                if let SelectedArea::Sequence(idx) = self.selected_area {
                                     self.sender.send(SetEvent::SetRangeStart((idx, 2))).unwrap()
                                 }
-                                _ => {}
-                            },
-                            Mode::RangeEnd => match self.selected_area {
-                                SelectedArea::Sequence(idx) => {
+                            }
+                            Mode::RangeEnd => {
+                                if let SelectedArea::Sequence(idx) = self.selected_area {
                                     self.sender.send(SetEvent::SetRangeEnd((idx, 2))).unwrap()
                                 }
-                                _ => {}
-                            },
+                            }
+                            Mode::Choke => {
+                                let group = match draw_data.choke_groups[2] {
+                                    None => Some(0),
+                                    Some(group) if group + 1 < CHOKE_GROUPS => Some(group + 1),
+                                    Some(_) => None,
+                                };
+                                self.sender
+                                    .send(SetEvent::SetChokeGroup((2, group)))
+                                    .unwrap()
+                            }
+                            Mode::Capture => self.sender.send(SetEvent::Capture(2)).unwrap(),
+                            Mode::Length => self
+                                .sender
+                                .send(SetEvent::SetRecordLength((
+                                    2,
+                                    draw_data.record_lengths[2].next(),
+                                )))
+                                .unwrap(),
+                            Mode::Reverse => self
+                                .sender
+                                .send(SetEvent::SetStepReverse((2, !draw_data.step_reverses[2])))
+                                .unwrap(),
+                            Mode::Edit => self.sender.send(SetEvent::Edit(2)).unwrap(),
+                            Mode::Undo => self.sender.send(SetEvent::Undo(2)).unwrap(),
                         },
                         KeyCode::Char('4') => match self.mode {
                             Mode::Record => self.sender.send(SetEvent::Record(3)).unwrap(),
                             Mode::Erase => self.sender.send(SetEvent::Erase(3)).unwrap(),
-                            Mode::RangeStart => match self.selected_area {
-                                SelectedArea::Sequence(idx) => {
+                            Mode::RangeStart => {
+                                if let SelectedArea::Sequence(idx) = self.selected_area {
                                     self.sender.send(SetEvent::SetRangeStart((idx, 3))).unwrap()
                                 }
-                                _ => {}
-                            },
-                            Mode::RangeEnd => match self.selected_area {
-                                SelectedArea::Sequence(idx) => {
+                            }
+                            Mode::RangeEnd => {
+                                if let SelectedArea::Sequence(idx) = self.selected_area {
                                     self.sender.send(SetEvent::SetRangeEnd((idx, 3))).unwrap()
                                 }
-                                _ => {}
-                            },
+                            }
+                            Mode::Choke => {
+                                let group = match draw_data.choke_groups[3] {
+                                    None => Some(0),
+                                    Some(group) if group + 1 < CHOKE_GROUPS => Some(group + 1),
+                                    Some(_) => None,
+                                };
+                                self.sender
+                                    .send(SetEvent::SetChokeGroup((3, group)))
+                                    .unwrap()
+                            }
+                            Mode::Capture => self.sender.send(SetEvent::Capture(3)).unwrap(),
+                            Mode::Length => self
+                                .sender
+                                .send(SetEvent::SetRecordLength((
+                                    3,
+                                    draw_data.record_lengths[3].next(),
+                                )))
+                                .unwrap(),
+                            Mode::Reverse => self
+                                .sender
+                                .send(SetEvent::SetStepReverse((3, !draw_data.step_reverses[3])))
+                                .unwrap(),
+                            Mode::Edit => self.sender.send(SetEvent::Edit(3)).unwrap(),
+                            Mode::Undo => self.sender.send(SetEvent::Undo(3)).unwrap(),
                         },
                         KeyCode::Char('5') => match self.mode {
                             Mode::Record => self.sender.send(SetEvent::Record(4)).unwrap(),
                             Mode::Erase => self.sender.send(SetEvent::Erase(4)).unwrap(),
-                            Mode::RangeStart => match self.selected_area {
-                                SelectedArea::Sequence(idx) => {
+                            Mode::RangeStart => {
+                                if let SelectedArea::Sequence(idx) = self.selected_area {
                                     self.sender.send(SetEvent::SetRangeStart((idx, 4))).unwrap()
                                 }
-                                _ => {}
-                            },
-                            Mode::RangeEnd => match self.selected_area {
-                                SelectedArea::Sequence(idx) => {
+                            }
+                            Mode::RangeEnd => {
+                                if let SelectedArea::Sequence(idx) = self.selected_area {
                                     self.sender.send(SetEvent::SetRangeEnd((idx, 4))).unwrap()
                                 }
-                                _ => {}
-                            },
+                            }
+                            Mode::Choke => {
+                                let group = match draw_data.choke_groups[4] {
+                                    None => Some(0),
+                                    Some(group) if group + 1 < CHOKE_GROUPS => Some(group + 1),
+                                    Some(_) => None,
+                                };
+                                self.sender
+                                    .send(SetEvent::SetChokeGroup((4, group)))
+                                    .unwrap()
+                            }
+                            Mode::Capture => self.sender.send(SetEvent::Capture(4)).unwrap(),
+                            Mode::Length => self
+                                .sender
+                                .send(SetEvent::SetRecordLength((
+                                    4,
+                                    draw_data.record_lengths[4].next(),
+                                )))
+                                .unwrap(),
+                            Mode::Reverse => self
+                                .sender
+                                .send(SetEvent::SetStepReverse((4, !draw_data.step_reverses[4])))
+                                .unwrap(),
+                            Mode::Edit => self.sender.send(SetEvent::Edit(4)).unwrap(),
+                            Mode::Undo => self.sender.send(SetEvent::Undo(4)).unwrap(),
                         },
                         KeyCode::Char('6') => match self.mode {
                             Mode::Record => self.sender.send(SetEvent::Record(5)).unwrap(),
                             Mode::Erase => self.sender.send(SetEvent::Erase(5)).unwrap(),
-                            Mode::RangeStart => match self.selected_area {
-                                SelectedArea::Sequence(idx) => {
+                            Mode::RangeStart => {
+                                if let SelectedArea::Sequence(idx) = self.selected_area {
                                     self.sender.send(SetEvent::SetRangeStart((idx, 5))).unwrap()
                                 }
-                                _ => {}
-                            },
-                            Mode::RangeEnd => match self.selected_area {
-                                SelectedArea::Sequence(idx) => {
+                            }
+                            Mode::RangeEnd => {
+                                if let SelectedArea::Sequence(idx) = self.selected_area {
                                     self.sender.send(SetEvent::SetRangeEnd((idx, 5))).unwrap()
                                 }
-                                _ => {}
-                            },
+                            }
+                            Mode::Choke => {
+                                let group = match draw_data.choke_groups[5] {
+                                    None => Some(0),
+                                    Some(group) if group + 1 < CHOKE_GROUPS => Some(group + 1),
+                                    Some(_) => None,
+                                };
+                                self.sender
+                                    .send(SetEvent::SetChokeGroup((5, group)))
+                                    .unwrap()
+                            }
+                            Mode::Capture => self.sender.send(SetEvent::Capture(5)).unwrap(),
+                            Mode::Length => self
+                                .sender
+                                .send(SetEvent::SetRecordLength((
+                                    5,
+                                    draw_data.record_lengths[5].next(),
+                                )))
+                                .unwrap(),
+                            Mode::Reverse => self
+                                .sender
+                                .send(SetEvent::SetStepReverse((5, !draw_data.step_reverses[5])))
+                                .unwrap(),
+                            Mode::Edit => self.sender.send(SetEvent::Edit(5)).unwrap(),
+                            Mode::Undo => self.sender.send(SetEvent::Undo(5)).unwrap(),
                         },
                         KeyCode::Char('7') => match self.mode {
                             Mode::Record => self.sender.send(SetEvent::Record(6)).unwrap(),
                             Mode::Erase => self.sender.send(SetEvent::Erase(6)).unwrap(),
-                            Mode::RangeStart => match self.selected_area {
-                                SelectedArea::Sequence(idx) => {
+                            Mode::RangeStart => {
+                                if let SelectedArea::Sequence(idx) = self.selected_area {
                                     self.sender.send(SetEvent::SetRangeStart((idx, 6))).unwrap()
                                 }
-                                _ => {}
-                            },
-                            Mode::RangeEnd => match self.selected_area {
-                                SelectedArea::Sequence(idx) => {
+                            }
+                            Mode::RangeEnd => {
+                                if let SelectedArea::Sequence(idx) = self.selected_area {
                                     self.sender.send(SetEvent::SetRangeEnd((idx, 6))).unwrap()
                                 }
-                                _ => {}
-                            },
+                            }
+                            Mode::Choke => {
+                                let group = match draw_data.choke_groups[6] {
+                                    None => Some(0),
+                                    Some(group) if group + 1 < CHOKE_GROUPS => Some(group + 1),
+                                    Some(_) => None,
+                                };
+                                self.sender
+                                    .send(SetEvent::SetChokeGroup((6, group)))
+                                    .unwrap()
+                            }
+                            Mode::Capture => self.sender.send(SetEvent::Capture(6)).unwrap(),
+                            Mode::Length => self
+                                .sender
+                                .send(SetEvent::SetRecordLength((
+                                    6,
+                                    draw_data.record_lengths[6].next(),
+                                )))
+                                .unwrap(),
+                            Mode::Reverse => self
+                                .sender
+                                .send(SetEvent::SetStepReverse((6, !draw_data.step_reverses[6])))
+                                .unwrap(),
+                            Mode::Edit => self.sender.send(SetEvent::Edit(6)).unwrap(),
+                            Mode::Undo => self.sender.send(SetEvent::Undo(6)).unwrap(),
                         },
                         KeyCode::Char('8') => match self.mode {
                             Mode::Record => self.sender.send(SetEvent::Record(7)).unwrap(),
                             Mode::Erase => self.sender.send(SetEvent::Erase(7)).unwrap(),
-                            Mode::RangeStart => match self.selected_area {
-                                SelectedArea::Sequence(idx) => {
+                            Mode::RangeStart => {
+                                if let SelectedArea::Sequence(idx) = self.selected_area {
                                     self.sender.send(SetEvent::SetRangeStart((idx, 7))).unwrap()
                                 }
-                                _ => {}
-                            },
-                            Mode::RangeEnd => match self.selected_area {
-                                SelectedArea::Sequence(idx) => {
+                            }
+                            Mode::RangeEnd => {
+                                if let SelectedArea::Sequence(idx) = self.selected_area {
                                     self.sender.send(SetEvent::SetRangeEnd((idx, 7))).unwrap()
                                 }
-                                _ => {}
-                            },
+                            }
+                            Mode::Choke => {
+                                let group = match draw_data.choke_groups[7] {
+                                    None => Some(0),
+                                    Some(group) if group + 1 < CHOKE_GROUPS => Some(group + 1),
+                                    Some(_) => None,
+                                };
+                                self.sender
+                                    .send(SetEvent::SetChokeGroup((7, group)))
+                                    .unwrap()
+                            }
+                            Mode::Capture => self.sender.send(SetEvent::Capture(7)).unwrap(),
+                            Mode::Length => self
+                                .sender
+                                .send(SetEvent::SetRecordLength((
+                                    7,
+                                    draw_data.record_lengths[7].next(),
+                                )))
+                                .unwrap(),
+                            Mode::Reverse => self
+                                .sender
+                                .send(SetEvent::SetStepReverse((7, !draw_data.step_reverses[7])))
+                                .unwrap(),
+                            Mode::Edit => self.sender.send(SetEvent::Edit(7)).unwrap(),
+                            Mode::Undo => self.sender.send(SetEvent::Undo(7)).unwrap(),
                         },
-                        KeyCode::Char(' ') => match self.selected_area {
-                            SelectedArea::Sequence(idx) => {
+                        KeyCode::Char(' ') => {
+                            if let SelectedArea::Sequence(idx) = self.selected_area {
                                 self.sender.send(SetEvent::Toggle(idx)).unwrap()
                             }
-                            _ => {}
-                        },
+                        }
                         _ => {}
                     };
                 }